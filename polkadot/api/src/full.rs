@@ -140,6 +140,10 @@ impl<B: LocalBackend<Block>> PolkadotApi for Client<B, LocalCallExecutor<B, Nati
 		with_runtime!(self, at, || ::runtime::System::account_nonce(account))
 	}
 
+	fn batch_index(&self, at: &CheckedId, accounts: &[AccountId]) -> Result<Vec<Index>> {
+		with_runtime!(self, at, || accounts.iter().map(|account| ::runtime::System::account_nonce(*account)).collect())
+	}
+
 	fn lookup(&self, at: &Self::CheckedBlockId, address: Address) -> Result<Option<AccountId>> {
 		with_runtime!(self, at, || <::runtime::Staking as AuxLookup>::lookup(address).ok())
 	}
@@ -125,6 +125,14 @@ pub trait PolkadotApi {
 	/// Get the nonce (né index) of an account at a block.
 	fn index(&self, at: &Self::CheckedBlockId, account: AccountId) -> Result<Index>;
 
+	/// Get the nonces of several accounts at a block, in the order given.
+	///
+	/// Implementations able to read them all at once should override this; by default
+	/// each one is fetched through `index`.
+	fn batch_index(&self, at: &Self::CheckedBlockId, accounts: &[AccountId]) -> Result<Vec<Index>> {
+		accounts.iter().map(|account| self.index(at, *account)).collect()
+	}
+
 	/// Get the account id of an address at a block.
 	fn lookup(&self, at: &Self::CheckedBlockId, address: Address) -> Result<Option<AccountId>>;
 
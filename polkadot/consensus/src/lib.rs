@@ -74,7 +74,7 @@ use polkadot_primitives::{Hash, Block, BlockId, BlockNumber, Header, Timestamp};
 use polkadot_primitives::parachain::{Id as ParaId, Chain, DutyRoster, BlockData, Extrinsic as ParachainExtrinsic, CandidateReceipt};
 use polkadot_runtime::BareExtrinsic;
 use primitives::AuthorityId;
-use transaction_pool::{Ready, TransactionPool};
+use transaction_pool::{EvaluateAsOf, Ready, TransactionPool};
 use tokio_core::reactor::{Handle, Timeout, Interval};
 
 use futures::prelude::*;
@@ -222,11 +222,11 @@ fn timer_error(e: &::std::io::Error) -> Error {
 }
 
 /// Polkadot proposer factory.
-pub struct ProposerFactory<C, N, P> {
+pub struct ProposerFactory<C: PolkadotApi, N, P> {
 	/// The client instance.
 	pub client: Arc<C>,
 	/// The transaction pool.
-	pub transaction_pool: Arc<TransactionPool>,
+	pub transaction_pool: Arc<TransactionPool<C>>,
 	/// The backing network handle.
 	pub network: N,
 	/// Parachain collators.
@@ -319,7 +319,7 @@ pub struct Proposer<C: PolkadotApi, R, P> {
 	random_seed: Hash,
 	router: R,
 	table: Arc<SharedTable>,
-	transaction_pool: Arc<TransactionPool>,
+	transaction_pool: Arc<TransactionPool<C>>,
 }
 
 impl<C, R, P> bft::Proposer<Block> for Proposer<C, R, P>
@@ -495,6 +495,7 @@ impl<C, R, P> bft::Proposer<Block> for Proposer<C, R, P>
 
 	fn import_misbehavior(&self, misbehavior: Vec<(AuthorityId, bft::Misbehavior<Hash>)>) {
 		use bft::generic::Misbehavior as GenericMisbehavior;
+		use polkadot_api::CheckedBlockId;
 		use runtime_primitives::bft::{MisbehaviorKind, MisbehaviorReport};
 		use runtime_primitives::MaybeUnsigned;
 		use polkadot_runtime::{Call, Extrinsic, UncheckedExtrinsic, ConsensusCall};
@@ -549,7 +550,7 @@ impl<C, R, P> bft::Proposer<Block> for Proposer<C, R, P>
 			};
 			let uxt = UncheckedExtrinsic::new(extrinsic, signature);
 
-			self.transaction_pool.import_unchecked_extrinsic(uxt)
+			self.transaction_pool.import_unchecked_extrinsic(*self.parent_id.block_id(), uxt)
 				.expect("locally signed extrinsic is valid; qed");
 		}
 	}
@@ -618,7 +619,7 @@ pub struct CreateProposal<C: PolkadotApi, R, P: Collators>  {
 	parent_number: BlockNumber,
 	parent_id: C::CheckedBlockId,
 	client: Arc<C>,
-	transaction_pool: Arc<TransactionPool>,
+	transaction_pool: Arc<TransactionPool<C>>,
 	collation: CollationFetch<P, C>,
 	router: R,
 	table: Arc<SharedTable>,
@@ -640,7 +641,9 @@ impl<C, R, P> CreateProposal<C, R, P>
 		let mut block_builder = self.client.build_block(&self.parent_id, timestamp, candidates)?;
 
 		{
-			let readiness_evaluator = Ready::create(self.parent_id.clone(), &*self.client);
+			let readiness_evaluator = Ready::create(self.parent_id.clone(), &*self.client)
+				.with_block_number(self.parent_number)
+				.with_evaluate_as_of(EvaluateAsOf::NextBlock);
 			let mut unqueue_invalid = Vec::new();
 			self.transaction_pool.cull_and_get_pending(readiness_evaluator, |pending_iterator| {
 				let mut pending_size = 0;
@@ -653,7 +656,16 @@ impl<C, R, P> CreateProposal<C, R, P>
 
 					if pending_size + pending.encoded_size() >= MAX_TRANSACTIONS_SIZE { break }
 
-					match block_builder.push_extrinsic(pending.primitive_extrinsic()) {
+					let primitive = match pending.primitive_extrinsic() {
+						Ok(primitive) => primitive,
+						Err(e) => {
+							trace!(target: "transaction-pool", "Invalid transaction: {}", e);
+							unqueue_invalid.push(pending.hash().clone());
+							continue
+						}
+					};
+
+					match block_builder.push_extrinsic(primitive) {
 						Ok(()) => {
 							pending_size += pending.encoded_size();
 						}
@@ -665,7 +677,7 @@ impl<C, R, P> CreateProposal<C, R, P>
 				}
 			});
 
-			self.transaction_pool.remove(&unqueue_invalid, false);
+			self.transaction_pool.remove_invalid(&unqueue_invalid);
 		}
 
 		let polkadot_block = block_builder.bake()?;
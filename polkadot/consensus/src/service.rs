@@ -223,6 +223,37 @@ fn start_bft<F, C>(
 	}
 }
 
+// check the justifications gossiped for agreement on top of `parent_hash` against its
+// authorities, without taking part in the agreement.
+fn observe_bft(
+	parent_hash: Hash,
+	handle: reactor::Handle,
+	client: &bft::Authorities<Block>,
+	network: &net::ConsensusService<Block>,
+) {
+	let authorities = match client.authorities(&BlockId::hash(parent_hash)) {
+		Ok(authorities) => authorities,
+		Err(e) => {
+			debug!("Error reading authorities: {:?}", e);
+			return;
+		}
+	};
+
+	let justifications = network.bft_messages(parent_hash)
+		.filter_map(|msg| match msg.message {
+			net::generic_message::BftMessage::Auxiliary(a) => Some(bft::UncheckedJustification::<Hash>::from(a)),
+			_ => None,
+		})
+		.for_each(move |justification| {
+			match bft::check_prepare_justification::<Block>(&authorities, parent_hash, justification) {
+				Ok(_) => info!(target: "bft", "Observed prepare justification on top of {}", parent_hash),
+				Err(_) => debug!(target: "bft", "Ignoring invalid justification on top of {}", parent_hash),
+			}
+			Ok(())
+		});
+	handle.spawn(justifications);
+}
+
 /// Consensus service. Starts working when created.
 pub struct Service {
 	thread: Option<thread::JoinHandle<()>>,
@@ -235,7 +266,7 @@ impl Service {
 		client: Arc<C>,
 		api: Arc<A>,
 		network: Arc<net::ConsensusService<Block>>,
-		transaction_pool: Arc<TransactionPool>,
+		transaction_pool: Arc<TransactionPool<A>>,
 		parachain_empty_duration: Duration,
 		key: ed25519::Pair,
 	) -> Service
@@ -315,6 +346,42 @@ impl Service {
 			exit_signal: Some(signal),
 		}
 	}
+
+	/// Create and start an instance observing agreement without taking part in it.
+	///
+	/// Needs neither a key nor local state: the justifications gossiped for agreement
+	/// on top of each new best block are checked against its authorities and logged.
+	pub fn new_observer<C>(
+		client: Arc<C>,
+		network: Arc<net::ConsensusService<Block>>,
+	) -> Service
+		where
+			C: BlockchainEvents<Block> + bft::Authorities<Block> + Send + Sync + 'static,
+	{
+		let (signal, exit) = ::exit_future::signal();
+		let thread = thread::spawn(move || {
+			let mut core = reactor::Core::new().expect("tokio::Core could not be created");
+
+			let notifications = {
+				let handle = core.handle();
+				client.import_notification_stream().for_each(move |notification| {
+					if notification.is_new_best {
+						observe_bft(notification.hash, handle.clone(), &*client, &*network);
+					}
+					Ok(())
+				})
+			};
+
+			core.handle().spawn(notifications);
+			if let Err(e) = core.run(exit) {
+				debug!("BFT observer event loop error {:?}", e);
+			}
+		});
+		Service {
+			thread: Some(thread),
+			exit_signal: Some(signal),
+		}
+	}
 }
 
 impl Drop for Service {
@@ -74,6 +74,11 @@ use runtime_primitives::{generic, traits::{HasPublicAux, BlakeTwo256, Convert}};
 pub use runtime_primitives::BuildStorage;
 
 pub use consensus::Call as ConsensusCall;
+pub use consensus::PrivCall as ConsensusPrivCall;
+pub use council::Call as CouncilCall;
+pub use council::voting::Call as CouncilVotingCall;
+pub use democracy::Call as DemocracyCall;
+pub use democracy::PrivCall as DemocracyPrivCall;
 pub use timestamp::Call as TimestampCall;
 pub use parachains::Call as ParachainsCall;
 pub use primitives::Header;
@@ -18,12 +18,15 @@
 
 use std::collections::HashMap;
 use std::sync::Arc;
-use client::{self, Client};
+use std::time::Duration;
+use bft;
+use client::{self, BlockchainEvents, Client};
 use client_db;
-use codec::{self, Slicable};
 use consensus;
+use ed25519;
 use keystore::Store as Keystore;
 use network;
+use parking_lot::Mutex;
 use polkadot_api;
 use runtime_primitives::MakeStorage;
 use polkadot_executor::Executor as LocalDispatch;
@@ -36,6 +39,12 @@ use error;
 /// Code executor.
 pub type CodeExecutor = NativeExecutor<LocalDispatch>;
 
+/// Block time used by consensus unless configured otherwise.
+pub const DEFAULT_BLOCK_TIME_MS: u64 = 4000;
+
+/// Time after which light clients drop relayed transactions, unless configured otherwise.
+pub const DEFAULT_LIGHT_TRANSACTION_TTL_SECS: u64 = 600;
+
 /// Polkadot service components.
 pub trait Components {
 	/// Client backend type.
@@ -51,22 +60,83 @@ pub trait Components {
 	fn build_client(&self, settings: client_db::DatabaseSettings, executor: CodeExecutor, genesis_storage: MakeStorage)
 		-> Result<(Arc<Client<Self::Backend, Self::Executor, Block>>, Option<Arc<network::OnDemand<Block, network::Service<Block>>>>), error::Error>;
 
+	/// Options of the transaction pool.
+	fn transaction_pool_options(&self) -> transaction_pool::Options {
+		Default::default()
+	}
+
 	/// Create api.
 	fn build_api(&self, client: Arc<Client<Self::Backend, Self::Executor, Block>>) -> Arc<Self::Api>;
 
 	/// Create network transaction pool adapter.
-	fn build_network_tx_pool(&self, client: Arc<client::Client<Self::Backend, Self::Executor, Block>>, api: Arc<Self::Api>, tx_pool: Arc<TransactionPool>)
+	fn build_network_tx_pool(&self, client: Arc<client::Client<Self::Backend, Self::Executor, Block>>, api: Arc<Self::Api>, tx_pool: Arc<TransactionPool<Self::Api>>)
 		-> Arc<network::TransactionPool<Block>>;
 
 	/// Create consensus service.
-	fn build_consensus(&self, client: Arc<Client<Self::Backend, Self::Executor, Block>>, network: Arc<network::Service<Block>>, tx_pool: Arc<TransactionPool>, keystore: &Keystore)
+	fn build_consensus(&self, client: Arc<Client<Self::Backend, Self::Executor, Block>>, network: Arc<network::Service<Block>>, tx_pool: Arc<TransactionPool<Self::Api>>, keystore: &Keystore)
 		-> Result<Option<consensus::Service>, error::Error>;
 }
 
+/// Selection of the key to author blocks with among those in the keystore.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuthorityKey {
+	/// The key with the given public part.
+	Public(ed25519::Public),
+	/// The key at the given position in the keystore's contents.
+	Index(usize),
+}
+
+/// Select the authority key among the keystore's `keys`, the first one if no
+/// selection is given.
+pub fn select_authority_key(keys: &[ed25519::Public], selection: Option<&AuthorityKey>) -> Result<ed25519::Public, error::Error> {
+	if keys.is_empty() {
+		bail!(error::ErrorKind::NoAuthorityKeys);
+	}
+
+	match selection {
+		None => Ok(keys[0].clone()),
+		Some(&AuthorityKey::Public(ref public)) => keys.iter()
+			.find(|key| *key == public)
+			.cloned()
+			.ok_or_else(|| error::ErrorKind::AuthorityKeyNotFound(public.to_ss58check()).into()),
+		Some(&AuthorityKey::Index(index)) => keys.get(index)
+			.cloned()
+			.ok_or_else(|| error::ErrorKind::AuthorityKeyNotFound(format!("#{}", index)).into()),
+	}
+}
+
 /// Components for full Polkadot service.
 pub struct FullComponents {
 	/// Is this a validator node?
 	pub is_validator: bool,
+	/// Key to author blocks with; the first one in the keystore if unset.
+	pub authority_key: Option<AuthorityKey>,
+	/// Time between blocks, `DEFAULT_BLOCK_TIME_MS` if unset.
+	pub block_time: Option<Duration>,
+	/// Options of the transaction pool.
+	pub transaction_pool: transaction_pool::Options,
+	/// Whether to import transactions received from the network, rather than only
+	/// those submitted locally.
+	pub accept_external_transactions: bool,
+}
+
+impl FullComponents {
+	// the adapter between the network and `pool`, importing external transactions only
+	// if configured to.
+	fn network_tx_pool<B, E, A>(&self, client: Arc<Client<B, E, Block>>, api: Arc<A>, pool: Arc<TransactionPool<A>>)
+		-> TransactionPoolAdapter<B, E, A> where
+		A: polkadot_api::PolkadotApi + Send + Sync,
+		E: Send + Sync,
+	{
+		TransactionPoolAdapter {
+			imports_external_transactions: self.accept_external_transactions,
+			verifies_transactions: true,
+			ready_cache: Default::default(),
+			pool,
+			client,
+			api,
+		}
+	}
 }
 
 impl Components for FullComponents {
@@ -79,42 +149,69 @@ impl Components for FullComponents {
 		Ok((Arc::new(client_db::new_client(db_settings, executor, genesis_storage)?), None))
 	}
 
+	fn transaction_pool_options(&self) -> transaction_pool::Options {
+		self.transaction_pool.clone()
+	}
+
 	fn build_api(&self, client: Arc<client::Client<Self::Backend, Self::Executor, Block>>) -> Arc<Self::Api> {
 		client
 	}
 
-	fn build_network_tx_pool(&self, client: Arc<client::Client<Self::Backend, Self::Executor, Block>>, api: Arc<Self::Api>, pool: Arc<TransactionPool>)
+	fn build_network_tx_pool(&self, client: Arc<client::Client<Self::Backend, Self::Executor, Block>>, api: Arc<Self::Api>, pool: Arc<TransactionPool<Self::Api>>)
 		-> Arc<network::TransactionPool<Block>> {
-		Arc::new(TransactionPoolAdapter {
-			imports_external_transactions: true,
-			pool,
-			client,
-			api,
-		})
+		Arc::new(self.network_tx_pool(client, api, pool))
 	}
 
-	fn build_consensus(&self, client: Arc<client::Client<Self::Backend, Self::Executor, Block>>, network: Arc<network::Service<Block>>, tx_pool: Arc<TransactionPool>, keystore: &Keystore)
+	fn build_consensus(&self, client: Arc<client::Client<Self::Backend, Self::Executor, Block>>, network: Arc<network::Service<Block>>, tx_pool: Arc<TransactionPool<Self::Api>>, keystore: &Keystore)
 		-> Result<Option<consensus::Service>, error::Error> {
 		if !self.is_validator {
 			return Ok(None);
 		}
 
-		// Load the first available key
-		let key = keystore.load(&keystore.contents()?[0], "")?;
+		let block_time = self.block_time.unwrap_or_else(|| Duration::from_millis(DEFAULT_BLOCK_TIME_MS));
+		if block_time == Duration::from_millis(0) {
+			bail!(error::ErrorKind::ZeroBlockTime);
+		}
+
+		let public = select_authority_key(&keystore.contents()?, self.authority_key.as_ref())?;
+		let key = keystore.load(&public, "")?;
 		info!("Using authority key {:?}", key.public());
 		Ok(Some(consensus::Service::new(
 			client.clone(),
 			client.clone(),
 			network.clone(),
 			tx_pool.clone(),
-			::std::time::Duration::from_millis(4000), // TODO: dynamic
+			block_time,
 			key,
 		)))
 	}
 }
 
 /// Components for light Polkadot service.
-pub struct LightComponents;
+pub struct LightComponents {
+	/// Options of the transaction pool, which can be kept small since light
+	/// clients don't author blocks. Transactions expire after
+	/// `DEFAULT_LIGHT_TRANSACTION_TTL_SECS` unless a `ttl` is set.
+	pub transaction_pool: transaction_pool::Options,
+	/// Accept transactions and relay them, checking only what doesn't need chain state.
+	pub relay_transactions: bool,
+	/// Observe consensus, checking the justifications received without taking part.
+	pub observe_consensus: bool,
+}
+
+impl LightComponents {
+	// light clients have neither a key nor the local state to take part in consensus,
+	// but may observe it.
+	fn consensus_observer<C>(&self, client: Arc<C>, network: Arc<network::ConsensusService<Block>>) -> Option<consensus::Service> where
+		C: BlockchainEvents<Block> + bft::Authorities<Block> + Send + Sync + 'static,
+	{
+		if self.observe_consensus {
+			Some(consensus::Service::new_observer(client, network))
+		} else {
+			None
+		}
+	}
+}
 
 impl Components for LightComponents {
 	type Backend = client::light::Backend<Block>;
@@ -130,64 +227,203 @@ impl Components for LightComponents {
 		Ok((Arc::new(client), Some(fetcher)))
 	}
 
+	fn transaction_pool_options(&self) -> transaction_pool::Options {
+		// without block bodies or local state, relayed transactions are hardly ever
+		// pruned as included, so they have to expire for the pool not to fill up.
+		let mut options = self.transaction_pool.clone();
+		if options.ttl.is_none() {
+			options.ttl = Some(Duration::from_secs(DEFAULT_LIGHT_TRANSACTION_TTL_SECS));
+		}
+		options
+	}
+
 	fn build_api(&self, client: Arc<client::Client<Self::Backend, Self::Executor, Block>>) -> Arc<Self::Api> {
 		Arc::new(polkadot_api::light::RemotePolkadotApiWrapper(client.clone()))
 	}
 
-	fn build_network_tx_pool(&self, client: Arc<client::Client<Self::Backend, Self::Executor, Block>>, api: Arc<Self::Api>, pool: Arc<TransactionPool>)
+	fn build_network_tx_pool(&self, client: Arc<client::Client<Self::Backend, Self::Executor, Block>>, api: Arc<Self::Api>, pool: Arc<TransactionPool<Self::Api>>)
 		-> Arc<network::TransactionPool<Block>> {
 		Arc::new(TransactionPoolAdapter {
-			imports_external_transactions: false,
+			imports_external_transactions: self.relay_transactions,
+			verifies_transactions: false,
+			ready_cache: Default::default(),
 			pool,
 			client,
 			api,
 		})
 	}
 
-	fn build_consensus(&self, _client: Arc<client::Client<Self::Backend, Self::Executor, Block>>, _network: Arc<network::Service<Block>>, _tx_pool: Arc<TransactionPool>, _keystore: &Keystore)
+	fn build_consensus(&self, client: Arc<client::Client<Self::Backend, Self::Executor, Block>>, network: Arc<network::Service<Block>>, _tx_pool: Arc<TransactionPool<Self::Api>>, _keystore: &Keystore)
 		-> Result<Option<consensus::Service>, error::Error> {
-		Ok(None)
+		Ok(self.consensus_observer(client, network))
+	}
+}
+
+// A pooled transaction in the form it's gossiped in, or `None` if it can't be converted.
+fn gossiped(xt: &transaction_pool::VerifiedTransaction) -> Option<(Hash, Vec<u8>)> {
+	match xt.primitive_extrinsic() {
+		Ok(primitive) => Some((*xt.hash(), primitive)),
+		Err(e) => {
+			warn!("Not propagating transaction {:?}: {}", xt.hash(), e);
+			None
+		}
+	}
+}
+
+// Import a transaction received from the network in its gossiped form, verifying it at
+// the best block if `verify` is set. Returns its hash if it is, or already was, pooled.
+fn import_external<A, F>(pool: &TransactionPool<A>, verify: bool, best_block: F, transaction: &[u8]) -> Option<Hash> where
+	A: polkadot_api::PolkadotApi,
+	F: FnOnce() -> Option<BlockId>,
+{
+	let uxt = match transaction_pool::decode_primitive_extrinsic(transaction) {
+		Ok(uxt) => uxt,
+		Err(_) => {
+			debug!("Error decoding transaction");
+			return None;
+		}
+	};
+
+	let imported = if verify {
+		match best_block() {
+			Some(best_block) => pool.import_unchecked_extrinsic(best_block, uxt),
+			None => {
+				debug!("Not importing transaction: best block unknown");
+				return None;
+			}
+		}
+	} else {
+		pool.import_unverified(uxt)
+	};
+
+	match imported {
+		Ok(xt) => Some(*xt.hash()),
+		Err(e) => match *e.kind() {
+			transaction_pool::ErrorKind::AlreadyImported(hash) => Some(hash[..].into()),
+			// belongs into a block's inherent slot; not worth reporting.
+			transaction_pool::ErrorKind::IsInherent(_) => None,
+			_ => {
+				debug!("Error adding transaction to the pool: {:?}", e);
+				None
+			},
+		}
+	}
+}
+
+// The transactions last found ready at the best block, reused until either the best
+// block or the pool changes.
+#[derive(Default)]
+struct ReadyCache {
+	// best block hash, pool generation and the transactions to propagate.
+	cached: Mutex<Option<(Hash, u64, Vec<(Hash, Vec<u8>)>)>>,
+}
+
+impl ReadyCache {
+	// get the cached transactions if computed at the same best block and pool generation,
+	// otherwise compute them afresh. Nothing is cached if `compute` fails.
+	fn get_or_compute<F>(&self, best_hash: Hash, generation: u64, compute: F) -> Vec<(Hash, Vec<u8>)> where
+		F: FnOnce() -> Option<Vec<(Hash, Vec<u8>)>>,
+	{
+		let mut cached = self.cached.lock();
+		if let Some((hash, gen, ref transactions)) = *cached {
+			if hash == best_hash && gen == generation {
+				return transactions.clone();
+			}
+		}
+
+		match compute() {
+			Some(transactions) => {
+				*cached = Some((best_hash, generation, transactions.clone()));
+				transactions
+			}
+			None => Vec::new(),
+		}
 	}
 }
 
 /// Transaction pool adapter.
-pub struct TransactionPoolAdapter<B, E, A> where A: Send + Sync, E: Send + Sync {
+pub struct TransactionPoolAdapter<B, E, A> where A: polkadot_api::PolkadotApi + Send + Sync, E: Send + Sync {
 	imports_external_transactions: bool,
-	pool: Arc<TransactionPool>,
+	// whether transactions are checked against chain state, or just relayed.
+	verifies_transactions: bool,
+	ready_cache: ReadyCache,
+	pool: Arc<TransactionPool<A>>,
 	client: Arc<Client<B, E, Block>>,
 	api: Arc<A>,
 }
 
-impl<B, E, A> network::TransactionPool<Block> for TransactionPoolAdapter<B, E, A>
+impl<B, E, A> TransactionPoolAdapter<B, E, A>
 	where
 		B: client::backend::Backend<Block> + Send + Sync,
 		E: client::CallExecutor<Block> + Send + Sync,
 		client::error::Error: From<<<B as client::backend::Backend<Block>>::State as state_machine::backend::Backend>::Error>,
 		A: polkadot_api::PolkadotApi + Send + Sync,
 {
-	fn transactions(&self) -> Vec<(Hash, Vec<u8>)> {
-		let best_block = match self.client.info() {
-			Ok(info) => info.chain.best_hash,
+	fn best_hash(&self) -> Option<Hash> {
+		match self.client.info() {
+			Ok(info) => Some(info.chain.best_hash),
 			Err(e) => {
 				debug!("Error getting best block: {:?}", e);
-				return Vec::new();
+				None
 			}
-		};
+		}
+	}
 
-		let id = match self.api.check_id(BlockId::hash(best_block)) {
-			Ok(id) => id,
-			Err(_) => return Vec::new(),
+	fn best_block(&self) -> Option<BlockId> {
+		self.best_hash().map(BlockId::hash)
+	}
+
+	/// Get the transactions to propagate as judged at `block` rather than the best block,
+	/// e.g. when following a fork.
+	pub fn transactions_at(&self, block: BlockId) -> Vec<(Hash, Vec<u8>)> {
+		if !self.verifies_transactions {
+			return self.pool.all_transactions()
+				.into_iter()
+				.filter_map(|t| gossiped(&t))
+				.collect();
+		}
+
+		match self.pool.pending_at(block) {
+			Ok(pending) => pending.into_iter().filter_map(|t| gossiped(&t)).collect(),
+			Err(e) => {
+				debug!("Error getting transactions at {:?}: {:?}", block, e);
+				Vec::new()
+			}
+		}
+	}
+}
+
+impl<B, E, A> network::TransactionPool<Block> for TransactionPoolAdapter<B, E, A>
+	where
+		B: client::backend::Backend<Block> + Send + Sync,
+		E: client::CallExecutor<Block> + Send + Sync,
+		client::error::Error: From<<<B as client::backend::Backend<Block>>::State as state_machine::backend::Backend>::Error>,
+		A: polkadot_api::PolkadotApi + Send + Sync,
+{
+	fn transactions(&self) -> Vec<(Hash, Vec<u8>)> {
+		if !self.verifies_transactions {
+			return self.pool.all_transactions()
+				.into_iter()
+				.filter_map(|t| gossiped(&t))
+				.collect();
+		}
+
+		let best_hash = match self.best_hash() {
+			Some(best_hash) => best_hash,
+			None => return Vec::new(),
 		};
 
-		let ready = transaction_pool::Ready::create(id, &*self.api);
+		// read before computing, so that changes made meanwhile aren't missed.
+		let generation = self.pool.generation();
+		self.ready_cache.get_or_compute(best_hash, generation, || {
+			let id = self.api.check_id(BlockId::hash(best_hash)).ok()?;
+			let ready = transaction_pool::Ready::create(id, &*self.api);
 
-		self.pool.cull_and_get_pending(ready, |pending| pending
-			.map(|t| {
-				let hash = t.hash().clone();
-				(hash, t.primitive_extrinsic())
-			})
-			.collect()
-		)
+			Some(self.pool.cull_and_get_pending(ready, |pending| pending
+				.filter_map(|t| gossiped(&t))
+				.collect()
+			))
+		})
 	}
 
 	fn import(&self, transaction: &Vec<u8>) -> Option<Hash> {
@@ -195,25 +431,219 @@ impl<B, E, A> network::TransactionPool<Block> for TransactionPoolAdapter<B, E, A
 			return None;
 		}
 
-		let encoded = transaction.encode();
-		if let Some(uxt) = codec::Slicable::decode(&mut &encoded[..]) {
-			match self.pool.import_unchecked_extrinsic(uxt) {
-				Ok(xt) => Some(*xt.hash()),
-				Err(e) => match *e.kind() {
-					transaction_pool::ErrorKind::AlreadyImported(hash) => Some(hash[..].into()),
-					_ => {
-						debug!("Error adding transaction to the pool: {:?}", e);
-						None
-					},
-				}
-			}
-		} else {
-			debug!("Error decoding transaction");
-			None
-		}
+		import_external(&self.pool, self.verifies_transactions, || self.best_block(), transaction)
 	}
 
 	fn on_broadcasted(&self, propagations: HashMap<Hash, Vec<String>>) {
 		self.pool.on_broadcasted(propagations)
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use client::LocalCallExecutor;
+	use client::in_mem::Backend as InMemory;
+	use codec::Slicable;
+	use futures::sync::mpsc;
+	use keyring::Keyring;
+	use network::TransactionPool as NetworkTransactionPool;
+	use polkadot_runtime::{BareExtrinsic, BuildStorage, Call, ConsensusConfig, Extrinsic, GenesisConfig, RawAddress,
+		SessionConfig, TimestampCall, UncheckedExtrinsic};
+	use primitives::AuthorityId;
+	use runtime_primitives::MaybeUnsigned;
+	use runtime_primitives::traits::{BlakeTwo256, Hashing};
+	use substrate_executor::NativeExecutionDispatch;
+
+	type InMemClient = Client<InMemory<Block>, LocalCallExecutor<InMemory<Block>, CodeExecutor>, Block>;
+
+	fn client() -> Arc<InMemClient> {
+		let authorities = vec![Keyring::One.to_raw_public(), Keyring::Two.to_raw_public()];
+		let genesis_config = GenesisConfig {
+			consensus: Some(ConsensusConfig {
+				code: LocalDispatch::native_equivalent().to_vec(),
+				authorities: authorities.clone(),
+			}),
+			system: None,
+			session: Some(SessionConfig {
+				validators: authorities.into_iter().map(Into::into).collect(),
+				session_length: 100,
+			}),
+			council: Some(Default::default()),
+			democracy: Some(Default::default()),
+			parachains: Some(Default::default()),
+			staking: Some(Default::default()),
+		};
+
+		Arc::new(client::new_in_mem(LocalDispatch::new(), genesis_config.build_storage()).unwrap())
+	}
+
+	// a transaction of Alice's, with its hash and in the form it's gossiped in.
+	fn gossiped_transaction() -> (Hash, Vec<u8>) {
+		let extrinsic = BareExtrinsic {
+			signed: Keyring::Alice.to_raw_public().into(),
+			index: 0,
+			function: Call::Timestamp(TimestampCall::set(0)),
+		};
+		let signature = extrinsic.using_encoded(|e| Keyring::Alice.sign(e));
+		let uxt = UncheckedExtrinsic::new(Extrinsic {
+			signed: RawAddress::Id(extrinsic.signed),
+			index: extrinsic.index,
+			function: extrinsic.function,
+		}, MaybeUnsigned(signature.into()));
+
+		let mut gossiped = uxt.extrinsic.encode();
+		uxt.signature.using_encoded(|s| gossiped.extend_from_slice(s));
+		(BlakeTwo256::hash(&uxt.encode()), gossiped)
+	}
+
+	// a chain importing no blocks, and a network gossiping no messages.
+	struct Idle;
+
+	impl BlockchainEvents<Block> for Idle {
+		fn import_notification_stream(&self) -> client::BlockchainEventStream<Block> {
+			mpsc::unbounded().1
+		}
+	}
+
+	impl bft::Authorities<Block> for Idle {
+		fn authorities(&self, _at: &BlockId) -> Result<Vec<AuthorityId>, bft::Error> {
+			Ok(Vec::new())
+		}
+	}
+
+	impl network::ConsensusService<Block> for Idle {
+		fn connect_to_authorities(&self, _addresses: &[String]) {}
+		fn bft_messages(&self, _parent_hash: Hash) -> network::BftMessageStream<Block> {
+			mpsc::unbounded().1
+		}
+		fn send_bft_message(&self, _message: network::LocalizedBftMessage<Block>) {}
+	}
+
+	fn light(observe_consensus: bool) -> LightComponents {
+		LightComponents { transaction_pool: Default::default(), relay_transactions: false, observe_consensus }
+	}
+
+	fn keys() -> Vec<ed25519::Public> {
+		(0..3).map(|i| ed25519::Public::from_raw([i; 32])).collect()
+	}
+
+	#[test]
+	fn empty_keystore_should_give_descriptive_error() {
+		match *select_authority_key(&[], None).unwrap_err().kind() {
+			error::ErrorKind::NoAuthorityKeys => {},
+			ref e => panic!("unexpected error: {:?}", e),
+		}
+	}
+
+	#[test]
+	fn authority_key_should_be_selectable() {
+		let keys = keys();
+		assert_eq!(select_authority_key(&keys, None).unwrap(), keys[0]);
+		assert_eq!(select_authority_key(&keys, Some(&AuthorityKey::Index(2))).unwrap(), keys[2]);
+		assert_eq!(select_authority_key(&keys, Some(&AuthorityKey::Public(keys[1].clone()))).unwrap(), keys[1]);
+		assert!(select_authority_key(&keys, Some(&AuthorityKey::Index(3))).is_err());
+	}
+
+	#[test]
+	fn full_node_should_import_external_transactions_only_if_accepting_them() {
+		let client = client();
+		let adapter = |accept_external_transactions| {
+			let full = FullComponents {
+				is_validator: false,
+				authority_key: None,
+				block_time: None,
+				transaction_pool: Default::default(),
+				accept_external_transactions,
+			};
+			let pool = Arc::new(TransactionPool::new(Default::default(), client.clone()));
+			full.network_tx_pool(client.clone(), client.clone(), pool)
+		};
+		let (hash, gossiped) = gossiped_transaction();
+
+		assert_eq!(adapter(false).import(&gossiped), None);
+		assert_eq!(adapter(true).import(&gossiped), Some(hash));
+	}
+
+	#[test]
+	fn external_transaction_should_be_imported_at_the_best_block() {
+		let client = client();
+		let pool = TransactionPool::new(Default::default(), client.clone());
+		let (hash, gossiped) = gossiped_transaction();
+
+		assert_eq!(import_external(&pool, true, || Some(BlockId::number(0)), &gossiped), Some(hash));
+		assert_eq!(pool.all_transactions().iter().map(|t| *t.hash()).collect::<Vec<_>>(), vec![hash]);
+	}
+
+	#[test]
+	fn external_transaction_should_not_be_imported_without_a_best_block() {
+		let client = client();
+		let pool = TransactionPool::new(Default::default(), client.clone());
+		let (_, gossiped) = gossiped_transaction();
+
+		assert_eq!(import_external(&pool, true, || None, &gossiped), None);
+		assert!(pool.all_transactions().is_empty());
+	}
+
+	#[test]
+	fn light_client_should_not_observe_consensus_by_default() {
+		assert!(light(false).consensus_observer(Arc::new(Idle), Arc::new(Idle)).is_none());
+	}
+
+	#[test]
+	fn light_client_should_observe_consensus_when_configured() {
+		assert!(light(true).consensus_observer(Arc::new(Idle), Arc::new(Idle)).is_some());
+	}
+
+	#[test]
+	fn components_should_use_configured_pool_limits() {
+		let options = |max_count| transaction_pool::Options {
+			limits: transaction_pool::Limits { max_count, ..Default::default() },
+			..Default::default()
+		};
+
+		let full = FullComponents {
+			is_validator: false,
+			authority_key: None,
+			block_time: None,
+			transaction_pool: options(8192),
+			accept_external_transactions: true,
+		};
+		assert_eq!(full.transaction_pool_options(), options(8192));
+
+		let light = LightComponents { transaction_pool: options(64), relay_transactions: false, observe_consensus: false };
+		assert_eq!(light.transaction_pool_options(), transaction_pool::Options {
+			ttl: Some(Duration::from_secs(DEFAULT_LIGHT_TRANSACTION_TTL_SECS)),
+			..options(64)
+		});
+
+		let ttl = Some(Duration::from_secs(60));
+		let light = LightComponents { transaction_pool: transaction_pool::Options { ttl, ..options(64) }, relay_transactions: false, observe_consensus: false };
+		assert_eq!(light.transaction_pool_options().ttl, ttl);
+	}
+
+	#[test]
+	fn ready_transactions_should_be_computed_once_while_unchanged() {
+		use std::cell::Cell;
+
+		let cache = ReadyCache::default();
+		let computed = Cell::new(0);
+		let compute = || {
+			computed.set(computed.get() + 1);
+			Some(vec![([1; 32].into(), vec![1, 2, 3])])
+		};
+
+		let first = cache.get_or_compute([0; 32].into(), 1, &compute);
+		assert_eq!(cache.get_or_compute([0; 32].into(), 1, &compute), first);
+		assert_eq!(computed.get(), 1);
+
+		cache.get_or_compute([0; 32].into(), 2, &compute);
+		cache.get_or_compute([2; 32].into(), 2, &compute);
+		assert_eq!(computed.get(), 3);
+
+		// failures aren't remembered.
+		assert_eq!(cache.get_or_compute([3; 32].into(), 2, || None), vec![]);
+		cache.get_or_compute([3; 32].into(), 2, &compute);
+		assert_eq!(computed.get(), 4);
+	}
+}
@@ -16,7 +16,9 @@
 
 //! Service configuration.
 
+use std::time::Duration;
 use transaction_pool;
+use components::AuthorityKey;
 use runtime_primitives::MakeStorage;
 pub use network::Role;
 pub use network::NetworkConfiguration;
@@ -43,6 +45,19 @@ pub struct Configuration {
 	pub telemetry: Option<String>,
 	/// Node name.
 	pub name: String,
+	/// Time between blocks authored by this node, if validating. Defaults to 4 seconds.
+	pub block_time: Option<Duration>,
+	/// Key to author blocks with, if validating. Defaults to the first key in the keystore.
+	pub authority_key: Option<AuthorityKey>,
+	/// Whether a light client relays transactions it receives, checking only what
+	/// doesn't need chain state.
+	pub relay_light_transactions: bool,
+	/// Whether a light client observes consensus, checking the justifications it
+	/// receives without taking part.
+	pub observe_light_consensus: bool,
+	/// Whether a full node imports transactions received from the network, rather
+	/// than only those submitted locally. Defaults to true.
+	pub accept_external_transactions: bool,
 }
 
 impl Default for Configuration {
@@ -58,6 +73,11 @@ impl Default for Configuration {
 			genesis_storage: Box::new(Default::default),
 			telemetry: Default::default(),
 			name: "Anonymous".into(),
+			block_time: None,
+			authority_key: None,
+			relay_light_transactions: false,
+			observe_light_consensus: false,
+			accept_external_transactions: true,
 		}
 	}
 }
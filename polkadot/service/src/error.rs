@@ -28,5 +28,20 @@ error_chain! {
 	}
 
 	errors {
+		/// There are no keys in the keystore to author blocks with.
+		NoAuthorityKeys {
+			description("No authority keys in the keystore"),
+			display("No authority keys in the keystore; generate one or pass a seed"),
+		}
+		/// The selected authority key is not in the keystore.
+		AuthorityKeyNotFound(key: String) {
+			description("Authority key not found in the keystore"),
+			display("Authority key {} not found in the keystore", key),
+		}
+		/// The configured block time is zero.
+		ZeroBlockTime {
+			description("Block time must be non-zero"),
+			display("Block time must be non-zero"),
+		}
 	}
 }
@@ -21,6 +21,7 @@ extern crate futures;
 extern crate ed25519;
 extern crate clap;
 extern crate exit_future;
+extern crate parking_lot;
 extern crate tokio_timer;
 extern crate polkadot_primitives;
 extern crate polkadot_runtime;
@@ -28,6 +29,7 @@ extern crate polkadot_executor;
 extern crate polkadot_api;
 extern crate polkadot_consensus as consensus;
 extern crate polkadot_transaction_pool as transaction_pool;
+extern crate substrate_bft as bft;
 extern crate substrate_keystore as keystore;
 extern crate substrate_runtime_io as runtime_io;
 extern crate substrate_primitives as primitives;
@@ -41,6 +43,9 @@ extern crate tokio_core;
 extern crate substrate_client as client;
 extern crate substrate_client_db as client_db;
 
+#[cfg(test)]
+extern crate substrate_keyring as keyring;
+
 #[macro_use]
 extern crate substrate_telemetry;
 #[macro_use]
@@ -58,16 +63,18 @@ use std::sync::Arc;
 use std::thread;
 use futures::prelude::*;
 use tokio_core::reactor::Core;
-use transaction_pool::TransactionPool;
+use transaction_pool::{PoolEvent, TransactionPool};
 use keystore::Store as Keystore;
 use polkadot_api::PolkadotApi;
-use polkadot_primitives::{Block, BlockId, Hash};
+use polkadot_primitives::{Block, BlockId, BlockNumber, Hash, UncheckedExtrinsic};
+use runtime_primitives::traits::{BlakeTwo256, Hashing};
+use codec::Slicable;
 use client::{Client, BlockchainEvents};
 use network::ManageNetwork;
 use exit_future::Signal;
 
 pub use self::error::{ErrorKind, Error};
-pub use self::components::{Components, FullComponents, LightComponents};
+pub use self::components::{AuthorityKey, Components, FullComponents, LightComponents};
 pub use config::{Configuration, Role};
 
 /// Polkadot service.
@@ -75,20 +82,33 @@ pub struct Service<Components: components::Components> {
 	thread: Option<thread::JoinHandle<()>>,
 	client: Arc<Client<Components::Backend, Components::Executor, Block>>,
 	network: Arc<network::Service<Block>>,
-	transaction_pool: Arc<TransactionPool>,
+	transaction_pool: Arc<TransactionPool<Components::Api>>,
 	signal: Option<Signal>,
 	_consensus: Option<consensus::Service>,
 }
 
 /// Creates light client and register protocol with the network service
 pub fn new_light(config: Configuration) -> Result<Service<components::LightComponents>, error::Error> {
-	Service::new(components::LightComponents, config)
+	let transaction_pool = config.transaction_pool.clone();
+	let relay_transactions = config.relay_light_transactions;
+	let observe_consensus = config.observe_light_consensus;
+	Service::new(components::LightComponents { transaction_pool, relay_transactions, observe_consensus }, config)
 }
 
 /// Creates full client and register protocol with the network service
 pub fn new_full(config: Configuration) -> Result<Service<components::FullComponents>, error::Error> {
 	let is_validator = (config.roles & Role::VALIDATOR) == Role::VALIDATOR;
-	Service::new(components::FullComponents { is_validator }, config)
+	let block_time = config.block_time;
+	let authority_key = config.authority_key.clone();
+	let transaction_pool = config.transaction_pool.clone();
+	let accept_external_transactions = config.accept_external_transactions;
+	Service::new(components::FullComponents {
+		is_validator,
+		authority_key,
+		block_time,
+		transaction_pool,
+		accept_external_transactions,
+	}, config)
 }
 
 impl<Components> Service<Components>
@@ -127,7 +147,9 @@ impl<Components> Service<Components>
 		info!("Best block is #{}", best_header.number);
 		telemetry!("node.start"; "height" => best_header.number, "best" => ?best_header.hash());
 
-		let transaction_pool = Arc::new(TransactionPool::new(config.transaction_pool));
+		let transaction_pool = Arc::new(TransactionPool::new(components.transaction_pool_options(), api.clone()));
+		// submitted transactions are verified at the best block.
+		transaction_pool.note_best_block(best_header.hash());
 		let transaction_pool_adapter = components.build_network_tx_pool(client.clone(), api.clone(), transaction_pool.clone());
 		let network_params = network::Params {
 			config: network::ProtocolConfig {
@@ -157,11 +179,36 @@ impl<Components> Service<Components>
 				// block notifications
 				let network1 = network.clone();
 				let txpool1 = txpool.clone();
+				let client1 = client.clone();
 
 				let events = client.import_notification_stream()
 					.for_each(move |notification| {
 						network1.on_block_imported(notification.hash, &notification.header);
-						prune_imported(&*api, &*txpool1, notification.hash);
+						if notification.is_new_best {
+							txpool1.note_best_block(notification.hash);
+							txpool1.remove_expired();
+						}
+						let body = match client1.body(&BlockId::hash(notification.hash)) {
+							Ok(body) => body.unwrap_or_default(),
+							Err(e) => {
+								warn!("Failed to fetch body of imported block: {:?}", e);
+								Vec::new()
+							}
+						};
+						prune_imported(&*api, &*txpool1, notification.hash, notification.header.number, &body);
+						Ok(())
+					});
+				core.handle().spawn(events);
+
+				// re-gossip transactions once they become ready
+				let network2 = network.clone();
+				let events = txpool.subscribe()
+					.filter(|event| match *event {
+						PoolEvent::BecameReady(_) => true,
+						_ => false,
+					})
+					.for_each(move |_| {
+						network2.trigger_repropagate();
 						Ok(())
 					});
 				core.handle().spawn(events);
@@ -210,23 +257,35 @@ impl<Components> Service<Components>
 	}
 
 	/// Get shared transaction pool instance.
-	pub fn transaction_pool(&self) -> Arc<TransactionPool> {
+	pub fn transaction_pool(&self) -> Arc<TransactionPool<Components::Api>> {
 		self.transaction_pool.clone()
 	}
 }
 
-/// Produce a task which prunes any finalized transactions from the pool.
-pub fn prune_imported<A>(api: &A, pool: &TransactionPool, hash: Hash)
+/// Produce a task which prunes any finalized transactions from the pool, given the
+/// extrinsics of the imported block, if known.
+pub fn prune_imported<A>(api: &A, pool: &TransactionPool<A>, hash: Hash, number: BlockNumber, body: &[UncheckedExtrinsic])
 	where
 		A: PolkadotApi,
 {
+	// pooled transactions are hashed over their full encoding.
+	let included = body.iter()
+		.filter_map(|xt| transaction_pool::decode_primitive_extrinsic(xt).ok())
+		.map(|uxt| BlakeTwo256::hash(&uxt.encode()))
+		.collect::<Vec<_>>();
+	pool.prune_included(&included);
+
 	match api.check_id(BlockId::hash(hash)) {
 		Ok(id) => {
-			let ready = transaction_pool::Ready::create(id, api);
+			let ready = transaction_pool::Ready::create(id, api).with_block_number(number);
 			pool.cull(None, ready);
 		},
 		Err(e) => warn!("Failed to check block id: {:?}", e),
 	}
+
+	if let Err(e) = pool.drop_missed_ready_deadlines(BlockId::hash(hash), number) {
+		warn!("Failed to enforce transaction ready deadlines: {:?}", e);
+	}
 }
 
 impl<Components> Drop for Service<Components> where Components: components::Components {
@@ -15,12 +15,17 @@
 // along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
 
 use extrinsic_pool::{self, txpool};
-use primitives::Hash;
+use polkadot_api;
+use primitives::{AccountId, Hash, Index};
 use runtime::{Address, UncheckedExtrinsic};
 
 error_chain! {
+	foreign_links {
+		Io(::std::io::Error) #[doc="IO error"];
+	}
 	links {
 		Pool(txpool::Error, txpool::ErrorKind);
+		Api(polkadot_api::Error, polkadot_api::ErrorKind);
 	}
 	errors {
 		/// Unexpected extrinsic format submitted
@@ -28,21 +33,67 @@ error_chain! {
 			description("Invalid extrinsic format."),
 			display("Invalid extrinsic format."),
 		}
+		/// Extrinsic decoded without consuming all of the input.
+		TrailingBytes(count: usize) {
+			description("Extrinsic is followed by trailing bytes."),
+			display("Extrinsic is followed by {} trailing bytes.", count),
+		}
 		/// Attempted to queue an inherent transaction.
 		IsInherent(xt: UncheckedExtrinsic) {
 			description("Inherent transactions cannot be queued."),
-			display("Inehrent transactions cannot be queued."),
+			display("Inherent transactions cannot be queued."),
 		}
-		/// Attempted to queue a transaction with bad signature.
+		/// Attempted to queue a transaction with bad signature. Never retried.
 		BadSignature(e: &'static str) {
 			description("Transaction had bad signature."),
 			display("Transaction had bad signature: {}", e),
 		}
+		/// Checking the transaction failed for a reason other than its signature or its
+		/// sender being unknown as yet, e.g. its address being invalid. Never retried.
+		CheckFailed(e: &'static str) {
+			description("Transaction check failed."),
+			display("Transaction check failed: {}", e),
+		}
 		/// Attempted to queue a transaction that is already in the pool.
 		AlreadyImported(hash: Hash) {
 			description("Transaction is already in the pool."),
 			display("Transaction {:?} is already in the pool.", hash),
 		}
+		/// Transaction was rejected by the pre-insert hook.
+		VetoedByHook(hash: Hash) {
+			description("Transaction was vetoed by the pre-insert hook."),
+			display("Transaction {:?} was vetoed by the pre-insert hook.", hash),
+		}
+		/// The transaction's index is too far ahead of its sender's on-chain index.
+		NonceTooFarAhead(index: Index, on_chain: Index) {
+			description("Transaction index is too far ahead of the sender's."),
+			display("Transaction index {} is too far ahead of the sender's index {}.", index, on_chain),
+		}
+		/// The sender already has as many transactions in the pool as allowed.
+		TooManyFromSender(who: Option<AccountId>) {
+			description("Sender has too many transactions in the pool."),
+			display("Sender {:?} has too many transactions in the pool.", who),
+		}
+		/// The sender of the transaction is banned.
+		Banned(who: AccountId) {
+			description("Transaction sender is banned."),
+			display("Sender {:?} is banned.", who),
+		}
+		/// The transaction's encoding exceeds the size allowed in the pool.
+		TooLarge(size: usize, limit: usize) {
+			description("Transaction is too large."),
+			display("Transaction of {} bytes exceeds the limit of {} bytes.", size, limit),
+		}
+		/// No best block is known to verify a submitted transaction against.
+		NoBestBlock {
+			description("No best block is known."),
+			display("No best block is known to verify the transaction against."),
+		}
+		/// No room could be made for the transaction.
+		PoolFull {
+			description("Transaction pool is full."),
+			display("Transaction pool is full."),
+		}
 		/// Import error.
 		Import(err: Box<::std::error::Error + Send>) {
 			description("Error importing transaction"),
@@ -61,6 +112,22 @@ error_chain! {
 	}
 }
 
+impl Error {
+	/// Whether the transaction was rejected for being inherent, i.e. belonging into a
+	/// block's inherent slot rather than the pool.
+	pub fn is_inherent(&self) -> bool {
+		self.inherent().is_some()
+	}
+
+	/// Get the transaction rejected for being inherent.
+	pub fn inherent(&self) -> Option<&UncheckedExtrinsic> {
+		match *self.kind() {
+			ErrorKind::IsInherent(ref xt) => Some(xt),
+			_ => None,
+		}
+	}
+}
+
 impl extrinsic_pool::api::Error for Error {
 	fn into_pool_error(self) -> ::std::result::Result<txpool::Error, Self> {
 		match self {
@@ -23,8 +23,9 @@ extern crate polkadot_runtime as runtime;
 extern crate polkadot_primitives as primitives;
 extern crate polkadot_api;
 extern crate parking_lot;
+extern crate futures;
 
-#[cfg(test)]
+#[cfg(any(test, feature = "test-helpers"))]
 extern crate substrate_keyring;
 
 #[macro_use]
@@ -36,28 +37,516 @@ extern crate log;
 mod error;
 
 use std::{
-	cmp::Ordering,
-	collections::{hash_map::Entry, HashMap},
-	ops::Deref,
-	sync::Arc,
+	cmp::{self, Ordering},
+	mem,
+	collections::{hash_map::Entry, BTreeMap, HashMap, HashSet, VecDeque},
+	sync::{Arc, Weak, atomic::{AtomicUsize, ATOMIC_USIZE_INIT, Ordering as AtomicOrdering}},
+	fmt,
+	io::{Read, Write},
+	time::{Duration, Instant},
 	result
 };
-use parking_lot::Mutex;
+use futures::{future, Future, Poll, Stream};
+use futures::sync::mpsc;
+use parking_lot::{Condvar, Mutex, RwLock};
 
 use codec::Slicable;
 use extrinsic_pool::{Pool, txpool::{self, Readiness, scoring::{Change, Choice}}};
 use extrinsic_pool::api::ExtrinsicPool;
-use polkadot_api::PolkadotApi;
-use primitives::{AccountId, AccountIndex, Hash, Index, UncheckedExtrinsic as FutureProofUncheckedExtrinsic};
-use runtime::{Address, RawAddress, UncheckedExtrinsic};
-use substrate_runtime_primitives::traits::{Bounded, Checkable, Hashing, BlakeTwo256};
+use polkadot_api::{CheckedBlockId, PolkadotApi};
+use primitives::{AccountId, AccountIndex, Balance, BlockId, BlockNumber, Hash, Index, UncheckedExtrinsic as FutureProofUncheckedExtrinsic};
+use primitives::parachain::{CandidateReceipt, Id as ParaId};
+use runtime::{Address, Call, ConsensusCall, ConsensusPrivCall, CouncilCall, CouncilVotingCall, DemocracyCall, DemocracyPrivCall,
+	ParachainsCall, PrivCall, RawAddress, UncheckedExtrinsic};
+use substrate_runtime_primitives::generic;
+use substrate_runtime_primitives::traits::{Checkable, Hashing, BlakeTwo256};
 
-pub use extrinsic_pool::txpool::{Options, Status, LightStatus, VerifiedTransaction as VerifiedTransactionOps};
+pub use extrinsic_pool::{Clock, ContentionStats};
+pub use extrinsic_pool::txpool::{Options as Limits, Status, LightStatus, VerifiedTransaction as VerifiedTransactionOps};
 pub use error::{Error, ErrorKind, Result};
 
 /// Type alias for convenience.
 pub type CheckedExtrinsic = <UncheckedExtrinsic as Checkable>::Checked;
 
+/// Outcome of checking a transaction's signature and sender.
+type CheckResult = result::Result<CheckedExtrinsic, &'static str>;
+
+/// The check error signalling that the sender couldn't be resolved yet.
+const UNAVAILABLE_MESSAGE: &'static str = "chain state not available";
+
+/// The check error of a signature not matching the sender, as given by the runtime.
+const BAD_SIGNATURE_MESSAGE: &'static str = "bad signature in extrinsic";
+
+/// Map the error of a failed check, other than `UNAVAILABLE_MESSAGE`, to the reason the
+/// transaction is rejected for good.
+fn check_error(e: &'static str) -> ErrorKind {
+	if e == BAD_SIGNATURE_MESSAGE {
+		ErrorKind::BadSignature(e)
+	} else {
+		ErrorKind::CheckFailed(e)
+	}
+}
+
+/// Default number of verification outcomes remembered by the pool.
+const DEFAULT_VERIFICATION_CACHE_SIZE: usize = 4096;
+
+/// Default maximal number of transactions evicted to make room for a single import.
+const DEFAULT_MAX_EVICTIONS_PER_IMPORT: usize = 16;
+
+/// Default maximal encoded size of a transaction. Larger ones wouldn't fit into a block.
+const DEFAULT_MAX_TRANSACTION_SIZE: usize = 4 * 1024 * 1024;
+
+/// Default number of unverified transactions retried by a single `retry_verification` call.
+const DEFAULT_MAX_RETRIES_PER_CALL: usize = 256;
+
+/// Fee density scores are fees per this many bytes, to keep precision for small fees.
+const FEE_DENSITY_BYTES: Balance = 1024;
+
+/// Score of a transaction whose sender isn't resolved yet.
+pub const UNVERIFIED_PRIORITY: u64 = 0;
+
+/// Score of an ordinary transaction under `ScoringMode::Flat`.
+pub const DEFAULT_PRIORITY: u64 = 1;
+
+/// Score of a misbehavior report. Reports only matter while the offence is recent,
+/// so they go ahead of everything else.
+pub const REPORT_PRIORITY: u64 = u64::max_value();
+
+/// Source of insertion ids, telling the order in which transactions were created.
+static NEXT_INSERTION_ID: AtomicUsize = ATOMIC_USIZE_INIT;
+static NEXT_WATCHER_ID: AtomicUsize = ATOMIC_USIZE_INIT;
+
+/// Transaction pool configuration.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Options {
+	/// Limits of the underlying pool. `limits.max_mem_usage` is enforced on every import,
+	/// evicting lower-scored transactions to make room where possible. `limits.max_per_sender`
+	/// counts per resolved account rather than per address; transactions whose sender isn't
+	/// resolved yet are counted together.
+	pub limits: Limits,
+	/// Maximal number of verification outcomes remembered between imports.
+	pub verification_cache_size: usize,
+	/// Whether readiness evaluators created by the pool should coalesce concurrent
+	/// index fetches for the same account and block into one request.
+	pub share_index_fetches: bool,
+	/// Maximal number of unverified transactions processed by a single
+	/// `retry_verification` call.
+	pub max_retries_per_call: usize,
+	/// Number of failed retries after which a transaction is quarantined, i.e. no
+	/// longer retried until released. Transactions are retried indefinitely if unset.
+	pub quarantine_after: Option<usize>,
+	/// Time to wait before retrying a transaction which failed verification, doubling
+	/// with every further failure. Transactions are retried on every call if unset.
+	pub retry_backoff: Option<Duration>,
+	/// Number of failed retries after which a transaction is dropped from the pool.
+	/// Takes precedence over `quarantine_after`. Transactions are kept if unset.
+	pub drop_unverified_after: Option<usize>,
+	/// Fee charged for every transaction, mirroring the staking module's base fee.
+	pub transaction_base_fee: Balance,
+	/// Fee charged for every byte of a transaction, mirroring the staking module's byte fee.
+	pub transaction_byte_fee: Balance,
+	/// How pooled transactions are prioritised.
+	pub scoring_mode: ScoringMode,
+	/// Whether a transaction may replace a pooled one with the same sender and index.
+	/// Otherwise both are kept, as they always were.
+	pub allow_replace: bool,
+	/// Age over which the retention priority of a future transaction halves when making
+	/// room in memory, so that old future transactions are evicted first. Future
+	/// transactions don't age if unset.
+	pub future_half_life: Option<Duration>,
+	/// Maximal number of lower-scored transactions evicted to make room in memory for
+	/// a single imported transaction.
+	pub max_evictions_per_import: usize,
+	/// How far ahead of the on-chain index a transaction's index may be before it is
+	/// considered stale, e.g. because the account was killed and its index reset. If set,
+	/// transactions of senders whose on-chain index is zero are stale too, unless
+	/// following pooled ones from index zero; see `Ready::with_max_nonce_ahead`.
+	pub max_nonce_ahead: Option<Index>,
+	/// How far ahead of the on-chain index a transaction's index may be to be imported,
+	/// so that a sender can't park transactions which won't be ready any time soon.
+	/// Unlimited if `None`.
+	///
+	/// Unlike `max_nonce_ahead`, which judges pooled transactions at every cull, this is
+	/// checked only on import. A gap above `max_nonce_ahead` has no use, as transactions
+	/// it lets in would be culled as stale.
+	pub max_nonce_gap: Option<Index>,
+	/// Whether a pool holding `limits.max_count` transactions rejects those which wouldn't
+	/// be ready at once, rather than letting them displace others. Ready transactions are
+	/// still taken in, evicting the lowest-priority future transaction, unless there is
+	/// none to evict.
+	pub reject_future_when_full: bool,
+	/// Time after which a transaction is dropped by `remove_expired`, whether it is
+	/// ready or not. Transactions are kept indefinitely if unset.
+	pub ttl: Option<Duration>,
+	/// Maximal encoded size of a transaction accepted into the pool.
+	pub max_transaction_size: usize,
+}
+
+impl Default for Options {
+	fn default() -> Self {
+		Options {
+			limits: Limits::default(),
+			verification_cache_size: DEFAULT_VERIFICATION_CACHE_SIZE,
+			share_index_fetches: false,
+			max_retries_per_call: DEFAULT_MAX_RETRIES_PER_CALL,
+			quarantine_after: None,
+			retry_backoff: None,
+			drop_unverified_after: None,
+			transaction_base_fee: 0,
+			transaction_byte_fee: 0,
+			scoring_mode: ScoringMode::Flat,
+			allow_replace: false,
+			future_half_life: None,
+			max_evictions_per_import: DEFAULT_MAX_EVICTIONS_PER_IMPORT,
+			max_nonce_ahead: None,
+			max_nonce_gap: None,
+			reject_future_when_full: false,
+			ttl: None,
+			max_transaction_size: DEFAULT_MAX_TRANSACTION_SIZE,
+		}
+	}
+}
+
+/// Decode an extrinsic, requiring the whole input to be consumed.
+///
+/// A valid extrinsic followed by garbage would otherwise be accepted under a hash
+/// differing from that of its actual encoding.
+pub fn decode_extrinsic(encoded: &[u8]) -> Result<UncheckedExtrinsic> {
+	let mut input = encoded;
+	let uxt = UncheckedExtrinsic::decode(&mut input).ok_or(ErrorKind::InvalidExtrinsicFormat)?;
+	if !input.is_empty() {
+		bail!(ErrorKind::TrailingBytes(input.len()));
+	}
+	Ok(uxt)
+}
+
+/// Decode an extrinsic in the form it's gossiped and submitted in, requiring the whole
+/// input to be consumed.
+///
+/// That form is the contents of a `primitives::UncheckedExtrinsic`, which lacks the
+/// length prefix `decode_extrinsic` expects.
+pub fn decode_primitive_extrinsic(xt: &[u8]) -> Result<UncheckedExtrinsic> {
+	let mut input = xt;
+	let extrinsic = Slicable::decode(&mut input).ok_or(ErrorKind::InvalidExtrinsicFormat)?;
+	let signature = Slicable::decode(&mut input).ok_or(ErrorKind::InvalidExtrinsicFormat)?;
+	if !input.is_empty() {
+		bail!(ErrorKind::TrailingBytes(input.len()));
+	}
+	Ok(UncheckedExtrinsic::new(extrinsic, signature))
+}
+
+// `UncheckedExtrinsic` is meant to share its encoding with `primitives::UncheckedExtrinsic`,
+// but that is only an assumption, which may not hold after a runtime upgrade.
+fn primitive_from_encoded(encoded: &[u8]) -> Result<::primitives::UncheckedExtrinsic> {
+	Slicable::decode(&mut &encoded[..]).ok_or_else(|| ErrorKind::InvalidExtrinsicFormat.into())
+}
+
+/// The pool's view of a single account.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountOverview {
+	/// Index of the next transaction expected from the account on chain.
+	pub next_index: Index,
+	/// Hashes of the account's transactions ready for inclusion, in order.
+	pub ready: Vec<Hash>,
+	/// Hashes of the account's transactions waiting for earlier ones, in order.
+	pub future: Vec<Hash>,
+	/// Indices missing between the next index and the account's latest transaction.
+	pub gaps: Vec<Index>,
+	/// Fees payable for all of the account's pooled transactions.
+	pub queued_fees: Balance,
+}
+
+/// An immutable snapshot of the pool, classified at a given block.
+///
+/// Queries against the snapshot are unaffected by later changes to the pool.
+#[derive(Debug, Clone)]
+pub struct FrozenPool {
+	at: BlockId,
+	ready: Vec<Arc<VerifiedTransaction>>,
+	future: Vec<Arc<VerifiedTransaction>>,
+}
+
+impl FrozenPool {
+	/// The block the snapshot was classified at.
+	pub fn at(&self) -> &BlockId {
+		&self.at
+	}
+
+	/// Transactions which were ready, in the order they would be included.
+	pub fn ready(&self) -> &[Arc<VerifiedTransaction>] {
+		&self.ready
+	}
+
+	/// Transactions which were not ready.
+	pub fn future(&self) -> &[Arc<VerifiedTransaction>] {
+		&self.future
+	}
+
+	/// Total number of transactions in the snapshot.
+	pub fn len(&self) -> usize {
+		self.ready.len() + self.future.len()
+	}
+
+	/// Whether the snapshot holds no transactions.
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+
+	/// Find a transaction in the snapshot by its hash.
+	pub fn find(&self, hash: &Hash) -> Option<&Arc<VerifiedTransaction>> {
+		self.ready.iter().chain(self.future.iter()).find(|xt| xt.hash() == hash)
+	}
+
+	/// Whether the transaction with given hash was ready.
+	pub fn is_ready(&self, hash: &Hash) -> bool {
+		self.ready.iter().any(|xt| xt.hash() == hash)
+	}
+
+	/// Get the transactions sent by `who`, ordered by index.
+	pub fn transactions_from(&self, who: &AccountId) -> Vec<Arc<VerifiedTransaction>> {
+		let mut xts = self.ready.iter()
+			.chain(self.future.iter())
+			.filter(|xt| xt.sender().ok().as_ref() == Some(who))
+			.cloned()
+			.collect::<Vec<_>>();
+		xts.sort_by_key(|xt| xt.index());
+		xts
+	}
+
+	/// Estimate the number of blocks until a ready transaction is included, like
+	/// `TransactionPool::estimated_blocks_until_inclusion`.
+	pub fn estimated_blocks_until_inclusion(&self, hash: &Hash, block_capacity: usize) -> Option<u32> {
+		if block_capacity == 0 {
+			return None;
+		}
+
+		let rank = self.ready.iter().position(|xt| xt.hash() == hash)?;
+		Some((rank / block_capacity) as u32)
+	}
+}
+
+/// State of an individual transaction in the pool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransactionState {
+	/// The transaction is ready for inclusion.
+	Ready,
+	/// The transaction waits for others to be included first.
+	Future,
+	/// The transaction can no longer be included, but hasn't been culled yet.
+	Stale,
+	/// The transaction isn't in the pool.
+	Unknown,
+}
+
+/// Outcome of checking a transaction with `TransactionPool::dry_run`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DryRunResult {
+	/// Hash of the transaction.
+	pub hash: Hash,
+	/// The state the transaction would be in if it were imported.
+	pub readiness: TransactionState,
+	/// The sender of the transaction, if it could be resolved.
+	pub sender: Option<AccountId>,
+}
+
+/// A limit of the pool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Constraint {
+	/// The number of transactions.
+	Count,
+	/// The memory used by transactions.
+	MemUsage,
+	/// The number of transactions from a single sender.
+	PerSender,
+}
+
+/// Remaining capacity of the pool in each of its limits.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Headroom {
+	/// Number of transactions which can still be added.
+	pub count: usize,
+	/// Memory which can still be used, in bytes.
+	pub mem_usage: usize,
+	/// Number of transactions which can still be added by the busiest sender.
+	pub per_sender: usize,
+	binding: Constraint,
+}
+
+impl Headroom {
+	/// The limit with the least capacity left, relative to its maximum.
+	pub fn binding(&self) -> Constraint {
+		self.binding
+	}
+}
+
+/// Outcome of importing a transaction with `TransactionPool::import_with_outcome`.
+#[derive(Debug, Clone)]
+pub struct ImportOutcome {
+	/// The imported transaction.
+	pub imported: Arc<VerifiedTransaction>,
+	/// The pooled transaction which the imported one replaced, as reported by the
+	/// underlying pool, e.g. one with the same sender and index.
+	pub replaced: Option<Arc<VerifiedTransaction>>,
+}
+
+/// Counts of the pool's operations since it was created.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct PoolMetricsSnapshot {
+	/// Transactions submitted for import.
+	pub submitted: usize,
+	/// Submitted transactions which passed verification, including their sender's address.
+	pub verified: usize,
+	/// Submitted transactions which failed verification or weren't admitted.
+	pub rejected: usize,
+	/// Submitted transactions which were in the pool already.
+	pub already_imported: usize,
+	/// Transactions culled for being stale.
+	pub culled: usize,
+	/// Transactions replaced by one with the same sender and index.
+	pub replaced: usize,
+	/// Transactions removed for being invalid.
+	pub removed: usize,
+}
+
+// Counters behind `PoolMetricsSnapshot`. They are only meant for monitoring, so
+// relaxed ordering will do.
+#[derive(Debug, Default)]
+struct PoolMetrics {
+	submitted: AtomicUsize,
+	verified: AtomicUsize,
+	rejected: AtomicUsize,
+	already_imported: AtomicUsize,
+	culled: AtomicUsize,
+	replaced: AtomicUsize,
+	removed: AtomicUsize,
+}
+
+impl PoolMetrics {
+	fn note(counter: &AtomicUsize, count: usize) {
+		counter.fetch_add(count, AtomicOrdering::Relaxed);
+	}
+
+	fn snapshot(&self) -> PoolMetricsSnapshot {
+		PoolMetricsSnapshot {
+			submitted: self.submitted.load(AtomicOrdering::Relaxed),
+			verified: self.verified.load(AtomicOrdering::Relaxed),
+			rejected: self.rejected.load(AtomicOrdering::Relaxed),
+			already_imported: self.already_imported.load(AtomicOrdering::Relaxed),
+			culled: self.culled.load(AtomicOrdering::Relaxed),
+			replaced: self.replaced.load(AtomicOrdering::Relaxed),
+			removed: self.removed.load(AtomicOrdering::Relaxed),
+		}
+	}
+}
+
+/// Outcome of a `retry_verification` call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetriedVerification {
+	/// Hashes of the transactions which are now fully verified.
+	pub promoted: Vec<Hash>,
+	/// Whether unverified transactions remain to be processed in the current sweep.
+	pub more_remaining: bool,
+	/// Hashes of the transactions dropped after failing verification too often, or
+	/// because their sender turned out to be banned.
+	pub dropped: Vec<Hash>,
+}
+
+/// Outcome of a `revalidate_at` call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RevalidationReport {
+	/// Number of transactions ready at the block.
+	pub ready: usize,
+	/// Number of transactions waiting for an earlier index or their sender to be resolved.
+	pub future: usize,
+	/// Number of stale transactions, which were culled.
+	pub stale: usize,
+	/// Number of transactions whose sender was resolved at the block.
+	pub promoted: usize,
+}
+
+/// A change to the contents of the pool, as reported to subscribers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PoolEvent {
+	/// The transaction was imported.
+	Imported(Hash),
+	/// The transaction was removed as invalid.
+	Removed(Hash),
+	/// The transaction was culled as stale.
+	Culled(Hash),
+	/// The sender of the transaction was resolved by `retry_verification`.
+	Promoted(Hash),
+	/// The transaction, future when imported or last judged, was found ready by
+	/// `cull_and_get_pending` or `revalidate_at`.
+	BecameReady(Hash),
+}
+
+/// A change in the state of a transaction, as reported to its `Watcher`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WatchEvent {
+	/// The transaction waits for others to be included first.
+	Future,
+	/// The transaction is ready for inclusion.
+	Ready,
+	/// The transaction was broadcast to the given peers.
+	Broadcast(Vec<String>),
+	/// The transaction left the pool without being found invalid, e.g. because it was
+	/// evicted, culled or included.
+	Dropped,
+	/// The transaction was removed as invalid.
+	Invalid,
+}
+
+// the watchers of a single transaction, along with the state last reported to them.
+#[derive(Default)]
+struct Watched {
+	state: Option<TransactionState>,
+	sinks: Vec<(usize, mpsc::UnboundedSender<WatchEvent>)>,
+}
+
+type Watchers = Mutex<HashMap<Hash, Watched>>;
+
+/// Stream of the changes in the state of a single transaction, created by
+/// `TransactionPool::watch`.
+///
+/// The stream ends once the transaction leaves the pool.
+pub struct Watcher {
+	hash: Hash,
+	id: usize,
+	receiver: mpsc::UnboundedReceiver<WatchEvent>,
+	watchers: Weak<Watchers>,
+}
+
+impl Stream for Watcher {
+	type Item = WatchEvent;
+	type Error = ();
+
+	fn poll(&mut self) -> Poll<Option<WatchEvent>, ()> {
+		self.receiver.poll()
+	}
+}
+
+impl Drop for Watcher {
+	fn drop(&mut self) {
+		let watchers = match self.watchers.upgrade() {
+			Some(watchers) => watchers,
+			None => return,
+		};
+
+		let mut watchers = watchers.lock();
+		let unwatched = match watchers.get_mut(&self.hash) {
+			Some(watched) => {
+				let id = self.id;
+				watched.sinks.retain(|&(other, _)| other != id);
+				watched.sinks.is_empty()
+			}
+			None => false,
+		};
+		if unwatched {
+			watchers.remove(&self.hash);
+		}
+	}
+}
+
 /// A verified transaction which should be includable and non-inherent.
 #[derive(Debug)]
 pub struct VerifiedTransaction {
@@ -66,7 +555,12 @@ pub struct VerifiedTransaction {
 	// call to `polish` is needed.
 	inner: Mutex<Option<CheckedExtrinsic>>,
 	hash: Hash,
-	encoded_size: usize,
+	// the encoding `hash` was computed over.
+	encoded: Vec<u8>,
+	insertion_id: usize,
+	spec_version: Option<u32>,
+	valid_till: Option<BlockNumber>,
+	imported_at: Instant,
 }
 
 impl Clone for VerifiedTransaction {
@@ -75,29 +569,65 @@ impl Clone for VerifiedTransaction {
 			original: self.original.clone(),
 			inner: Mutex::new(self.inner.lock().clone()),
 			hash: self.hash.clone(),
-			encoded_size: self.encoded_size.clone(),
+			encoded: self.encoded.clone(),
+			insertion_id: self.insertion_id,
+			spec_version: self.spec_version,
+			valid_till: self.valid_till,
+			imported_at: self.imported_at,
 		}
 	}
 }
 
 impl VerifiedTransaction {
-	/// Attempt to verify a transaction.
-	fn create(original: UncheckedExtrinsic) -> Result<Self> {
+	/// Attempt to verify a transaction no larger than `max_size` bytes once encoded.
+	fn create(original: UncheckedExtrinsic, max_size: usize) -> Result<Self> {
+		Self::create_with(original, max_size, |_, uxt| uxt.check(|a| match a {
+			RawAddress::Id(i) => Ok(i),
+			_ => Err(UNAVAILABLE_MESSAGE),
+		}))
+	}
+
+	/// Attempt to verify a transaction using the given check, which is handed the
+	/// transaction's hash along with the transaction itself. If the check fails with
+	/// `UNAVAILABLE_MESSAGE`, a call to `polish` is needed.
+	///
+	/// Transactions larger than `max_size` bytes once encoded are rejected before they
+	/// are hashed or checked.
+	fn create_with<F>(original: UncheckedExtrinsic, max_size: usize, check: F) -> Result<Self> where
+		F: FnOnce(&Hash, UncheckedExtrinsic) -> CheckResult
+	{
 		if !original.is_signed() {
 			bail!(ErrorKind::IsInherent(original))
 		}
-		const UNAVAILABLE_MESSAGE: &'static str = "chain state not available";
-		let (encoded_size, hash) = original.using_encoded(|e| (e.len(), BlakeTwo256::hash(e)));
-		let lookup = |a| match a {
-			RawAddress::Id(i) => Ok(i),
-			_ => Err(UNAVAILABLE_MESSAGE),
-		};
-		let inner = Mutex::new(match original.clone().check(lookup) {
+		let mut encoded = original.encode();
+		// kept for as long as the transaction is, so don't hold on to spare capacity.
+		encoded.shrink_to_fit();
+		if encoded.len() > max_size {
+			bail!(ErrorKind::TooLarge(encoded.len(), max_size))
+		}
+		let hash = BlakeTwo256::hash(&encoded);
+		let inner = Mutex::new(match check(&hash, original.clone()) {
 			Ok(xt) => Some(xt),
 			Err(e) if e == UNAVAILABLE_MESSAGE => None,
-			Err(e) => bail!(ErrorKind::BadSignature(e)),
+			Err(e) => bail!(check_error(e)),
 		});
-		Ok(VerifiedTransaction { original, inner, hash, encoded_size })
+		let insertion_id = NEXT_INSERTION_ID.fetch_add(1, AtomicOrdering::SeqCst);
+		Ok(VerifiedTransaction { original, inner, hash, encoded, insertion_id, spec_version: None, valid_till: None, imported_at: Instant::now() })
+	}
+
+	/// Create a verified transaction of `signer` with the given index and call.
+	///
+	/// Only meant for tests, which can place it into a pool with
+	/// `TransactionPool::import_verified` without mocking address resolution.
+	#[cfg(any(test, feature = "test-helpers"))]
+	pub fn for_test(signer: substrate_keyring::Keyring, index: Index, function: Call) -> Result<Self> {
+		let extrinsic = runtime::BareExtrinsic { signed: signer.to_raw_public().into(), index, function };
+		let signature = extrinsic.using_encoded(|e| signer.sign(e));
+		Self::create(UncheckedExtrinsic::new(runtime::Extrinsic {
+			signed: RawAddress::Id(extrinsic.signed),
+			index: extrinsic.index,
+			function: extrinsic.function,
+		}, substrate_runtime_primitives::MaybeUnsigned(signature.into())), usize::max_value())
 	}
 
 	/// If this transaction isn't really verified, verify it and morph it into a really verified
@@ -108,7 +638,7 @@ impl VerifiedTransaction {
 		let inner: result::Result<CheckedExtrinsic, Error> = self.original
 			.clone()
 			.check(lookup)
-			.map_err(|e| ErrorKind::BadSignature(e).into());
+			.map_err(|e| check_error(e).into());
 		*self.inner.lock() = Some(inner?);
 		Ok(())
 	}
@@ -118,15 +648,22 @@ impl VerifiedTransaction {
 		self.inner.lock().is_some()
 	}
 
+	/// Whether the transaction reports misbehavior of an authority.
+	pub fn is_misbehavior_report(&self) -> bool {
+		match self.original.extrinsic.function {
+			Call::Consensus(ConsensusCall::report_misbehavior(..)) => true,
+			_ => false,
+		}
+	}
+
 	/// Access the underlying transaction.
 	pub fn as_transaction(&self) -> &UncheckedExtrinsic {
 		&self.original
 	}
 
 	/// Convert to primitive unchecked extrinsic.
-	pub fn primitive_extrinsic(&self) -> ::primitives::UncheckedExtrinsic {
-		Slicable::decode(&mut self.as_transaction().encode().as_slice())
-			.expect("UncheckedExtrinsic shares repr with Vec<u8>; qed")
+	pub fn primitive_extrinsic(&self) -> Result<::primitives::UncheckedExtrinsic> {
+		primitive_from_encoded(&self.encoded)
 	}
 
 	/// Consume the verified transaciton, yielding the unchecked counterpart.
@@ -144,14 +681,90 @@ impl VerifiedTransaction {
 		self.inner.lock().as_ref().map(|i| i.signed.clone()).ok_or_else(|| ErrorKind::NotReady.into())
 	}
 
+	/// Resolve the sender of this transaction afresh at the given block.
+	///
+	/// Unlike `sender`, this reflects any reassignment of the transaction's address
+	/// since it was verified. Returns `None` if the address is unknown at that block.
+	pub fn resolve_sender<A: PolkadotApi>(&self, at: BlockId, api: &A) -> Result<Option<AccountId>> {
+		match self.original.extrinsic.signed {
+			RawAddress::Id(ref id) => Ok(Some(*id)),
+			ref address => {
+				let at = api.check_id(at)?;
+				Ok(api.lookup(&at, address.clone())?)
+			}
+		}
+	}
+
 	/// Get the account ID of the sender of this transaction.
 	pub fn index(&self) -> Index {
 		self.original.extrinsic.index
 	}
 
+	/// Get the tags this transaction provides once included: its sender along with its index.
+	pub fn provides(&self) -> Vec<Vec<u8>> {
+		vec![self.tag(self.index())]
+	}
+
+	/// Get the tags which need to be provided before this transaction can be included: its
+	/// sender along with the previous index, if any.
+	///
+	/// Whether the sender's first transaction in the pool is ready still depends on the
+	/// index on chain, which `Ready` checks.
+	pub fn requires(&self) -> Vec<Vec<u8>> {
+		match self.index().checked_sub(1) {
+			Some(previous) => vec![self.tag(previous)],
+			None => Vec::new(),
+		}
+	}
+
+	// the sender is given by its account ID if resolved, otherwise by its address.
+	fn tag(&self, index: Index) -> Vec<u8> {
+		let mut tag = match self.sender() {
+			Ok(id) => id.encode(),
+			Err(_) => self.original.extrinsic.signed.encode(),
+		};
+		index.using_encoded(|index| tag.extend(index));
+		tag
+	}
+
 	/// Get encoded size of the transaction.
 	pub fn encoded_size(&self) -> usize {
-		self.encoded_size
+		self.encoded.len()
+	}
+
+	/// Get the encoding of the transaction, exactly as hashed into `hash`.
+	pub fn encoded(&self) -> &[u8] {
+		&self.encoded
+	}
+
+	/// Get the runtime spec version this transaction was submitted for, if any.
+	pub fn spec_version(&self) -> Option<u32> {
+		self.spec_version
+	}
+
+	/// Get the time this transaction was verified for the pool.
+	pub fn imported_at(&self) -> Instant {
+		self.imported_at
+	}
+
+	/// Get the last block number this transaction may be included in, if it is mortal.
+	pub fn valid_till(&self) -> Option<BlockNumber> {
+		self.valid_till
+	}
+
+	/// Whether this transaction's validity window has passed at the given block number.
+	pub fn is_stale_by_era(&self, number: BlockNumber) -> bool {
+		self.valid_till.map_or(false, |till| number > till)
+	}
+
+	/// Get the fee payable for including this transaction under the given fee schedule.
+	pub fn fee(&self, base_fee: Balance, byte_fee: Balance) -> Balance {
+		base_fee + byte_fee * self.encoded_size() as Balance
+	}
+
+	/// Whether the stored hash still matches the hash of the original transaction.
+	pub fn has_intact_hash(&self) -> bool {
+		self.original.using_encoded(|e| BlakeTwo256::hash(e)) == self.hash
 	}
 }
 
@@ -168,24 +781,183 @@ impl txpool::VerifiedTransaction for VerifiedTransaction {
 	}
 
 	fn mem_usage(&self) -> usize {
-		1 // TODO
+		// the pool accounts for the usage on insertion and removal, so it must not change
+		// when `polish` fills in the checked extrinsic; the heap of its call, a copy of the
+		// original's, is counted up front.
+		mem::size_of::<Self>() + self.encoded.capacity() + 2 * call_heap_size(&self.original.extrinsic.function)
+	}
+}
+
+/// The most `mem_usage` counts for a transaction of at most `max_size` bytes once encoded.
+///
+/// No call holds more on the heap per encoded byte than a boxed proposal, which takes
+/// `size_of::<PrivCall>()` for the two bytes encoding its module and function.
+fn max_mem_usage(max_size: usize) -> usize {
+	mem::size_of::<VerifiedTransaction>()
+		.saturating_add(max_size)
+		.saturating_add(max_size.saturating_mul(mem::size_of::<PrivCall>()))
+}
+
+// the heap allocated by the arguments of a call.
+fn call_heap_size(call: &Call) -> usize {
+	match *call {
+		Call::Democracy(DemocracyCall::propose(ref proposal, _)) |
+		Call::CouncilVoting(CouncilVotingCall::propose(ref proposal)) =>
+			mem::size_of::<PrivCall>() + proposal_heap_size(proposal),
+		Call::Council(CouncilCall::set_approvals(ref votes, _)) => votes.capacity() * mem::size_of::<bool>(),
+		Call::Parachains(ParachainsCall::set_heads(ref heads)) =>
+			heads.capacity() * mem::size_of::<CandidateReceipt>() + heads.iter().map(candidate_heap_size).sum::<usize>(),
+		_ => 0,
+	}
+}
+
+// the heap allocated by the arguments of a proposal.
+fn proposal_heap_size(proposal: &PrivCall) -> usize {
+	match *proposal {
+		PrivCall::Consensus(ConsensusPrivCall::set_code(ref code)) => code.capacity(),
+		PrivCall::Consensus(ConsensusPrivCall::set_storage(ref items)) =>
+			items.capacity() * mem::size_of::<(Vec<u8>, Vec<u8>)>()
+				+ items.iter().map(|&(ref key, ref value)| key.capacity() + value.capacity()).sum::<usize>(),
+		PrivCall::Democracy(DemocracyPrivCall::start_referendum(ref proposal, _)) =>
+			mem::size_of::<PrivCall>() + proposal_heap_size(proposal),
+		_ => 0,
 	}
 }
 
+// the heap allocated by a candidate receipt.
+fn candidate_heap_size(candidate: &CandidateReceipt) -> usize {
+	candidate.head_data.0.capacity()
+		+ candidate.balance_uploads.capacity() * mem::size_of::<(AccountId, u64)>()
+		+ candidate.egress_queue_roots.capacity() * mem::size_of::<(ParaId, Hash)>()
+}
+
+/// How transactions are prioritised by `Scoring`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScoringMode {
+	/// All transactions score the same.
+	Flat,
+	/// Transactions score by the fee they pay per encoded byte, so that small
+	/// transactions are preferred over large ones paying the same fee.
+	FeeDensity,
+	/// Transactions score as set with `TransactionPool::set_score`, and `0` otherwise.
+	#[cfg(any(test, feature = "test-helpers"))]
+	Explicit,
+}
+
+/// Custom prioritisation of transactions, for chains wanting an ordering of their own.
+///
+/// Only takes effect through `Scoring::with_priority`, overriding the `ScoringMode`.
+pub trait Prioritise: fmt::Debug + Send + Sync {
+	/// Get the priority of a transaction; higher priorities are preferred.
+	///
+	/// Misbehavior reports and transactions whose sender isn't resolved yet are
+	/// prioritised by the pool regardless.
+	fn priority(&self, xt: &VerifiedTransaction) -> u64;
+}
+
 /// Scoring implementation for polkadot transactions.
-#[derive(Debug)]
-pub struct Scoring;
+#[derive(Debug, Clone)]
+pub struct Scoring {
+	mode: ScoringMode,
+	base_fee: Balance,
+	byte_fee: Balance,
+	allow_replace: bool,
+	#[cfg(any(test, feature = "test-helpers"))]
+	explicit_scores: Arc<Mutex<HashMap<Hash, u64>>>,
+	priority: Option<Arc<Prioritise>>,
+}
+
+impl Scoring {
+	/// Create a new scoring, charging fees according to the given schedule.
+	pub fn new(mode: ScoringMode, base_fee: Balance, byte_fee: Balance) -> Self {
+		Scoring {
+			mode,
+			base_fee,
+			byte_fee,
+			allow_replace: false,
+			#[cfg(any(test, feature = "test-helpers"))]
+			explicit_scores: Default::default(),
+			priority: None,
+		}
+	}
+
+	/// Score transactions with the given prioritisation rather than by the scoring mode.
+	pub fn with_priority(mut self, priority: Arc<Prioritise>) -> Self {
+		self.priority = Some(priority);
+		self
+	}
+
+	/// Let a transaction replace a pooled one with the same sender and index
+	/// instead of being kept alongside it.
+	pub fn with_allow_replace(mut self, allow_replace: bool) -> Self {
+		self.allow_replace = allow_replace;
+		self
+	}
+
+	/// Get the score of a transaction; higher scores are preferred.
+	pub fn score(&self, xt: &VerifiedTransaction) -> u64 {
+		if let Some(ref priority) = self.priority {
+			return priority.priority(xt);
+		}
+
+		match self.mode {
+			ScoringMode::Flat => DEFAULT_PRIORITY,
+			ScoringMode::FeeDensity => {
+				let fee = xt.fee(self.base_fee, self.byte_fee);
+				let density = fee.saturating_mul(FEE_DENSITY_BYTES) / ::std::cmp::max(xt.encoded_size(), 1) as Balance;
+				if density > u64::max_value() as Balance { u64::max_value() } else { density as u64 }
+			}
+			#[cfg(any(test, feature = "test-helpers"))]
+			ScoringMode::Explicit => self.explicit_scores.lock().get(&xt.hash).cloned().unwrap_or(0),
+		}
+	}
+
+	// the score a transaction is ranked by in the pool, taking precedence over the mode.
+	fn rank(&self, xt: &VerifiedTransaction) -> u64 {
+		if !xt.is_really_verified() {
+			UNVERIFIED_PRIORITY
+		} else if xt.is_misbehavior_report() {
+			REPORT_PRIORITY
+		} else {
+			self.score(xt)
+		}
+	}
+
+	/// Set the score of the transaction with the given hash, for `ScoringMode::Explicit`.
+	///
+	/// Scores are computed on import, so this only affects transactions imported afterwards.
+	#[cfg(any(test, feature = "test-helpers"))]
+	pub fn set_score(&self, hash: Hash, score: u64) {
+		self.explicit_scores.lock().insert(hash, score);
+	}
+}
 
 impl txpool::Scoring<VerifiedTransaction> for Scoring {
 	type Score = u64;
 	type Event = ();
 
 	fn compare(&self, old: &VerifiedTransaction, other: &VerifiedTransaction) -> Ordering {
-		old.index().cmp(&other.index())
+		// break ties between senders on their account, so that the order is total. those
+		// whose sender isn't resolved yet come first, by address. transactions of the same
+		// account compare equal at the same index, however either names it.
+		old.index().cmp(&other.index()).then_with(|| match (old.sender(), other.sender()) {
+			(Ok(old), Ok(other)) => old.cmp(&other),
+			(Err(_), Ok(_)) => Ordering::Less,
+			(Ok(_), Err(_)) => Ordering::Greater,
+			(Err(_), Err(_)) => {
+				let (old, other) = (&old.original.extrinsic.signed, &other.original.extrinsic.signed);
+				old.using_encoded(|old| other.using_encoded(|other| old.cmp(other)))
+			}
+		})
 	}
 
-	fn choose(&self, _old: &VerifiedTransaction, _new: &VerifiedTransaction) -> Choice {
-		Choice::InsertNew
+	fn choose(&self, old: &VerifiedTransaction, new: &VerifiedTransaction) -> Choice {
+		let same_index = old.index() == new.index() && old.is_really_verified() && new.is_really_verified();
+		if self.allow_replace && same_index {
+			Choice::ReplaceOld
+		} else {
+			Choice::InsertNew
+		}
 	}
 
 	fn update_scores(
@@ -195,35 +967,242 @@ impl txpool::Scoring<VerifiedTransaction> for Scoring {
 		_change: Change<()>
 	) {
 		for i in 0..xts.len() {
-			// all the same score since there are no fees.
-			// TODO: prioritize things like misbehavior or fishermen reports
-			scores[i] = 1;
+			scores[i] = self.rank(&xts[i].transaction);
+		}
+	}
+	fn should_replace(&self, old: &VerifiedTransaction, new: &VerifiedTransaction) -> bool {
+		// a sender's transaction can't push out one it has to follow, however either
+		// names the sender.
+		let same_sender = match (old.sender(), new.sender()) {
+			(Ok(old), Ok(new)) => old == new,
+			_ => VerifiedTransactionOps::sender(old) == VerifiedTransactionOps::sender(new),
+		};
+		if same_sender && old.index() < new.index() {
+			return false;
 		}
+
+		// only strictly better transactions get in, so equals never churn.
+		self.rank(new) > self.rank(old)
+	}
+}
+
+#[derive(Default)]
+struct IndexFetch {
+	result: Mutex<Option<Option<Index>>>,
+	done: Condvar,
+}
+
+// Publishes the result of a fetch to the evaluators waiting for it once dropped, so
+// that they are woken even if fetching panics, in which case the index is unknown.
+struct FetchLeader<'a> {
+	fetches: &'a IndexFetches,
+	pending: Arc<IndexFetch>,
+	index: Option<Index>,
+}
+
+impl<'a> Drop for FetchLeader<'a> {
+	fn drop(&mut self) {
+		*self.pending.result.lock() = Some(self.index);
+		self.pending.done.notify_all();
+		let pending = &self.pending;
+		self.fetches.in_flight.lock().retain(|&(_, _, ref p)| !Arc::ptr_eq(p, pending));
 	}
-	fn should_replace(&self, _old: &VerifiedTransaction, _new: &VerifiedTransaction) -> bool {
-		false // no fees to determine which is better.
+}
+
+/// Index fetches in progress, shared between readiness evaluators.
+///
+/// Concurrent evaluations needing the index of the same account at the same block
+/// are served by a single request. This matters on light clients, where every
+/// fetch goes to the network.
+#[derive(Default)]
+pub struct IndexFetches {
+	in_flight: Mutex<Vec<(BlockId, AccountId, Arc<IndexFetch>)>>,
+}
+
+impl IndexFetches {
+	/// Fetch the index of `who` at `block` using `fetch`, unless the same fetch is
+	/// already in flight, in which case wait for its result instead.
+	fn fetch<F>(&self, block: &BlockId, who: &AccountId, fetch: F) -> Option<Index> where
+		F: FnOnce() -> Option<Index>,
+	{
+		let (pending, is_leader) = {
+			let mut in_flight = self.in_flight.lock();
+			let existing = in_flight.iter()
+				.find(|&&(ref b, ref w, _)| b == block && w == who)
+				.map(|&(_, _, ref pending)| pending.clone());
+
+			match existing {
+				Some(pending) => (pending, false),
+				None => {
+					let pending = Arc::new(IndexFetch::default());
+					in_flight.push((*block, *who, pending.clone()));
+					(pending, true)
+				}
+			}
+		};
+
+		if is_leader {
+			let mut leader = FetchLeader { fetches: self, pending, index: None };
+			leader.index = fetch();
+			leader.index
+		} else {
+			let mut result = pending.result.lock();
+			while result.is_none() {
+				pending.done.wait(&mut result);
+			}
+			result.expect("only leaves the loop once the result is set; qed")
+		}
 	}
 }
 
+/// Height at which `Ready` evaluates validity depending on the block number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EvaluateAsOf {
+	/// The block readiness is evaluated at.
+	Current,
+	/// The block to be built on top of it, which is what matters when authoring: a
+	/// transaction expiring at the current block can't be included any more.
+	NextBlock,
+}
+
+/// Source of the accounts behind indexed addresses, for nodes which can't look them up
+/// in local state, e.g. light clients fetching them from full nodes.
+pub trait SenderFetcher {
+	/// Future resolving to the account behind an address, if any.
+	type Fetch: Future<Item=Option<AccountId>, Error=Error> + 'static;
+
+	/// Start fetching the account behind the address `index` at the given block.
+	fn fetch_sender(&self, at: BlockId, index: AccountIndex) -> Self::Fetch;
+}
+
 /// Readiness evaluator for polkadot transactions.
 pub struct Ready<'a, T: 'a + PolkadotApi> {
 	at_block: T::CheckedBlockId,
 	api: &'a T,
+	// next index expected from each sender in the current pass.
 	known_nonces: HashMap<AccountId, (::primitives::Index, bool)>,
 	known_indexes: HashMap<AccountIndex, AccountId>,
+	index_fetches: Option<Arc<IndexFetches>>,
+	max_nonce_ahead: Option<Index>,
+	block_number: Option<BlockNumber>,
+	evaluate_as_of: EvaluateAsOf,
+	// on-chain index of each sender at `at_block`, prefetched or looked up.
+	chain_nonces: HashMap<AccountId, Index>,
+	index_errors: usize,
 }
 
 impl<'a, T: 'a + PolkadotApi> Ready<'a, T> {
 	/// Create a new readiness evaluator at the given block. Requires that
 	/// the ID has already been checked for local corresponding and available state.
 	pub fn create(at: T::CheckedBlockId, api: &'a T) -> Self {
+		let block_number = match *at.block_id() {
+			generic::BlockId::Number(number) => Some(number),
+			generic::BlockId::Hash(_) => None,
+		};
 		Ready {
+			block_number,
+			evaluate_as_of: EvaluateAsOf::Current,
+			chain_nonces: HashMap::new(),
 			at_block: at,
 			api,
 			known_nonces: HashMap::new(),
 			known_indexes: HashMap::new(),
+			index_fetches: None,
+			max_nonce_ahead: None,
+			index_errors: 0,
+		}
+	}
+
+	/// Get the number of times fetching a sender's on-chain index failed.
+	///
+	/// Transactions of such senders are judged `Future`, and the index is fetched again
+	/// when they are evaluated next.
+	pub fn index_errors(&self) -> usize {
+		self.index_errors
+	}
+
+	/// Create a new readiness evaluator at the given block, with the indices of `senders`
+	/// fetched up front. Indices of other senders are still fetched when needed.
+	pub fn create_with_prefetch(at: T::CheckedBlockId, api: &'a T, senders: &[AccountId]) -> Self {
+		let mut ready = Self::create(at, api);
+		ready.prewarm(senders);
+		ready
+	}
+
+	/// Create a new readiness evaluator at the given block, taking `nonces` as the
+	/// on-chain indices of their accounts rather than fetching them.
+	///
+	/// Meant for light clients ordering their own transactions, which know their
+	/// indices better than the state they can reliably query.
+	pub fn with_known_nonces(at: T::CheckedBlockId, api: &'a T, nonces: HashMap<AccountId, Index>) -> Self {
+		let mut ready = Self::create(at, api);
+		ready.chain_nonces.extend(nonces);
+		ready
+	}
+
+	/// Fetch the indices of the given accounts up front, in a single batched call where
+	/// the API supports it, rather than one by one while evaluating readiness.
+	pub fn prewarm(&mut self, accounts: &[AccountId]) {
+		let accounts = accounts.iter()
+			.filter(|a| !self.chain_nonces.contains_key(a))
+			.cloned()
+			.collect::<Vec<_>>();
+		if accounts.is_empty() {
+			return;
+		}
+
+		match self.api.batch_index(&self.at_block, &accounts) {
+			Ok(nonces) => self.chain_nonces.extend(accounts.into_iter().zip(nonces)),
+			Err(e) => debug!(target: "transaction-pool", "Failed to prewarm indices: {:?}", e),
+		}
+	}
+
+	/// Start a new evaluation pass, expecting the on-chain index from every sender again.
+	///
+	/// Clones start a new pass too. On-chain indices are remembered across passes.
+	pub fn reset(&mut self) {
+		self.known_nonces.clear();
+	}
+
+	/// Set the number of the block readiness is evaluated at, for blocks given by hash.
+	///
+	/// Mortal transactions only expire when the number is known.
+	pub fn with_block_number(mut self, number: BlockNumber) -> Self {
+		self.block_number = Some(number);
+		self
+	}
+
+	/// Set the height validity depending on the block number is evaluated at;
+	/// `EvaluateAsOf::Current` by default.
+	pub fn with_evaluate_as_of(mut self, evaluate_as_of: EvaluateAsOf) -> Self {
+		self.evaluate_as_of = evaluate_as_of;
+		self
+	}
+
+	fn evaluated_number(&self) -> Option<BlockNumber> {
+		match self.evaluate_as_of {
+			EvaluateAsOf::Current => self.block_number,
+			EvaluateAsOf::NextBlock => self.block_number.map(|number| number + 1),
 		}
 	}
+
+	/// Consider transactions stale once their index is more than `max_nonce_ahead`
+	/// ahead of the next index expected on chain. This catches transactions of accounts
+	/// which were killed and had their index reset.
+	///
+	/// Transactions whose sender's on-chain index is zero, as for an account which no
+	/// longer exists, are considered stale however far ahead, unless they follow pooled
+	/// transactions from index zero.
+	pub fn with_max_nonce_ahead(mut self, max_nonce_ahead: Index) -> Self {
+		self.max_nonce_ahead = Some(max_nonce_ahead);
+		self
+	}
+
+	/// Coalesce index fetches with other evaluators sharing the same `IndexFetches`.
+	pub fn with_index_fetches(mut self, fetches: Arc<IndexFetches>) -> Self {
+		self.index_fetches = Some(fetches);
+		self
+	}
 }
 
 impl<'a, T: 'a + PolkadotApi> Clone for Ready<'a, T> {
@@ -231,8 +1210,14 @@ impl<'a, T: 'a + PolkadotApi> Clone for Ready<'a, T> {
 		Ready {
 			at_block: self.at_block.clone(),
 			api: self.api,
-			known_nonces: self.known_nonces.clone(),
+			known_nonces: HashMap::new(),
 			known_indexes: self.known_indexes.clone(),
+			index_fetches: self.index_fetches.clone(),
+			max_nonce_ahead: self.max_nonce_ahead,
+			block_number: self.block_number,
+			evaluate_as_of: self.evaluate_as_of,
+			chain_nonces: self.chain_nonces.clone(),
+			index_errors: self.index_errors,
 		}
 	}
 }
@@ -240,6 +1225,10 @@ impl<'a, T: 'a + PolkadotApi> Clone for Ready<'a, T> {
 impl<'a, T: 'a + PolkadotApi> txpool::Ready<VerifiedTransaction> for Ready<'a, T>
 {
 	fn is_ready(&mut self, xt: &VerifiedTransaction) -> Readiness {
+		if self.evaluated_number().map_or(false, |number| xt.is_stale_by_era(number)) {
+			return Readiness::Stale
+		}
+
 		if !xt.is_really_verified() {
 			let id = match xt.original.extrinsic.signed.clone() {
 				RawAddress::Id(id) => id.clone(),	// should never happen, since we're not verified.
@@ -275,22 +1264,51 @@ impl<'a, T: 'a + PolkadotApi> txpool::Ready<VerifiedTransaction> for Ready<'a, T
 
 		let is_index_sender = match xt.original.extrinsic.signed { RawAddress::Index(_) => false, _ => true };
 
-		// TODO: find a way to handle index error properly -- will need changes to
-		// transaction-pool trait.
-		let (api, at_block) = (&self.api, &self.at_block);
-		let get_nonce = || api.index(at_block, sender).ok().unwrap_or_else(Bounded::max_value);
-		let (next_nonce, was_index_sender) = self.known_nonces.entry(sender).or_insert_with(|| (get_nonce(), is_index_sender));
+		let cached_nonce = self.chain_nonces.get(&sender).cloned();
+		let chain_nonce = match cached_nonce {
+			Some(nonce) => nonce,
+			None => {
+				let (api, at_block) = (&self.api, &self.at_block);
+				let fetch = || match api.index(at_block, sender) {
+					Ok(nonce) => Some(nonce),
+					Err(e) => {
+						warn!(target: "transaction-pool", "Failed to fetch index of {} at {:?}: {}", Hash::from(sender), at_block.block_id(), e);
+						None
+					}
+				};
+				let fetched = match self.index_fetches {
+					Some(ref fetches) => fetches.fetch(at_block.block_id(), &sender, fetch),
+					None => fetch(),
+				};
+				match fetched {
+					Some(nonce) => {
+						self.chain_nonces.insert(sender, nonce);
+						nonce
+					}
+					None => {
+						// not remembered, so the fetch is retried rather than the
+						// transaction parked for good.
+						self.index_errors += 1;
+						return Readiness::Future;
+					}
+				}
+			}
+		};
+		let (next_nonce, was_index_sender) = self.known_nonces.entry(sender).or_insert((chain_nonce, is_index_sender));
 
 		trace!(target: "transaction-pool", "Next index for sender is {}; xt index is {}", next_nonce, xt.original.extrinsic.index);
 
-		if *was_index_sender == is_index_sender || get_nonce() == *next_nonce {
+		if *was_index_sender == is_index_sender || chain_nonce == *next_nonce {
 			match xt.original.extrinsic.index.cmp(&next_nonce) {
-				Ordering::Greater => Readiness::Future,
+				Ordering::Greater => match self.max_nonce_ahead {
+					// likely the account was reset, or killed and never sent anything
+					// since, so this can never become ready.
+					Some(max) if xt.original.extrinsic.index - *next_nonce > max || *next_nonce == 0 => Readiness::Stale,
+					_ => Readiness::Future,
+				},
 				Ordering::Less => Readiness::Stale,
 				Ordering::Equal => {
 					// remember to increment `next_nonce`
-					// TODO: this won't work perfectly since accounts can now be killed, returning the nonce
-					// to zero.
 					*next_nonce = next_nonce.saturating_add(1);
 					Readiness::Ready
 				}
@@ -302,302 +1320,4475 @@ impl<'a, T: 'a + PolkadotApi> txpool::Ready<VerifiedTransaction> for Ready<'a, T
 	}
 }
 
-pub struct Verifier;
+#[derive(Clone)]
+pub struct Verifier {
+	max_transaction_size: usize,
+	banned: Arc<RwLock<HashSet<AccountId>>>,
+}
+
+impl Verifier {
+	/// Cheaply reject transactions which are obviously forged, before spending a state
+	/// lookup on them: inherents, oversized ones and, where the sender is given by id,
+	/// those whose signature doesn't match.
+	///
+	/// Signatures of transactions from indexed addresses can't be checked without
+	/// resolving the address, so they pass; full verification is still needed.
+	pub fn quick_screen(&self, uxt: &UncheckedExtrinsic) -> Result<()> {
+		if !uxt.is_signed() {
+			bail!(ErrorKind::IsInherent(uxt.clone()));
+		}
+
+		let size = uxt.using_encoded(|e| e.len());
+		if size > self.max_transaction_size {
+			bail!(ErrorKind::TooLarge(size, self.max_transaction_size));
+		}
+
+		if let RawAddress::Id(_) = uxt.extrinsic.signed {
+			uxt.clone().check(|a| match a {
+				RawAddress::Id(i) => Ok(i),
+				_ => Err(UNAVAILABLE_MESSAGE),
+			}).map_err(check_error)?;
+		}
+		Ok(())
+	}
+}
 
 impl txpool::Verifier<UncheckedExtrinsic> for Verifier {
 	type VerifiedTransaction = VerifiedTransaction;
 	type Error = Error;
 
-	fn verify_transaction(&self, uxt: UncheckedExtrinsic) -> Result<Self::VerifiedTransaction> {
-		info!("Extrinsic Submitted: {:?}", uxt);
-		VerifiedTransaction::create(uxt)
+	fn verify_transaction(&self, uxt: UncheckedExtrinsic) -> Result<Self::VerifiedTransaction> {
+		trace!(target: "transaction-pool", "Extrinsic submitted: {:?}", uxt);
+		let verified = VerifiedTransaction::create(uxt, self.max_transaction_size)
+			.and_then(|xt| reject_banned(xt, &self.banned));
+		if let Err(ref e) = verified {
+			log_rejection(None, e);
+		}
+		verified
+	}
+}
+
+/// Summarise why a transaction was rejected, which is worth noting unlike a submission.
+fn log_rejection(hash: Option<&Hash>, e: &Error) {
+	// inherents get gossiped routinely, and are never meant for the pool.
+	if e.is_inherent() {
+		debug!(target: "transaction-pool", "{}", e);
+		return;
+	}
+
+	match hash {
+		Some(hash) => info!(target: "transaction-pool", "Rejected transaction {}: {}", hash, e),
+		None => info!(target: "transaction-pool", "Rejected transaction: {}", e),
+	}
+}
+
+/// Reject `xt` if its sender is known to be banned.
+fn reject_banned(xt: VerifiedTransaction, banned: &RwLock<HashSet<AccountId>>) -> Result<VerifiedTransaction> {
+	match xt.sender() {
+		Ok(sender) if banned.read().contains(&sender) => bail!(ErrorKind::Banned(sender)),
+		_ => Ok(xt),
+	}
+}
+
+/// Bounded cache of verification outcomes, keyed by transaction hash.
+///
+/// Address resolution depends on chain state, so outcomes are only reused at the
+/// block they were computed at. Caching an outcome for a different block flushes
+/// everything computed before.
+#[derive(Debug)]
+struct VerificationCache {
+	at_block: Option<BlockId>,
+	outcomes: HashMap<Hash, CheckResult>,
+	order: VecDeque<Hash>,
+	capacity: usize,
+}
+
+impl VerificationCache {
+	fn new(capacity: usize) -> Self {
+		VerificationCache {
+			at_block: None,
+			outcomes: HashMap::new(),
+			order: VecDeque::new(),
+			capacity,
+		}
+	}
+
+	/// Get the outcome of verifying the given transaction at the given block, if known.
+	fn get(&self, block: &BlockId, hash: &Hash) -> Option<CheckResult> {
+		if self.at_block.as_ref() != Some(block) {
+			return None;
+		}
+		self.outcomes.get(hash).cloned()
+	}
+
+	/// Remember the outcome of verifying a transaction at the given block.
+	fn insert(&mut self, block: &BlockId, hash: Hash, outcome: CheckResult) {
+		if self.at_block.as_ref() != Some(block) {
+			self.outcomes.clear();
+			self.order.clear();
+			self.at_block = Some(*block);
+		}
+
+		if self.outcomes.insert(hash, outcome).is_none() {
+			self.order.push_back(hash);
+		}
+
+		while self.order.len() > self.capacity {
+			if let Some(oldest) = self.order.pop_front() {
+				self.outcomes.remove(&oldest);
+			}
+		}
+	}
+
+	/// Forget the outcome of verifying the given transaction.
+	fn remove(&mut self, hash: &Hash) {
+		if self.outcomes.remove(hash).is_some() {
+			self.order.retain(|h| h != hash);
+		}
+	}
+
+	/// Forget everything if it was computed at a block given by number, which may
+	/// refer to a different block after a reorganisation.
+	fn forget_numbered(&mut self) {
+		if is_numbered(&self.at_block) {
+			*self = VerificationCache::new(self.capacity);
+		}
+	}
+}
+
+/// Account IDs of addresses looked up at a single block.
+///
+/// Looking up an address at a different block flushes the mappings of the previous one,
+/// since indices may be reassigned.
+#[derive(Debug, Default)]
+struct AddressCache {
+	at_block: Option<BlockId>,
+	ids: HashMap<Address, Option<AccountId>>,
+}
+
+impl AddressCache {
+	/// Get the result of looking up the given address at the given block, if known.
+	fn get(&self, block: &BlockId, address: &Address) -> Option<Option<AccountId>> {
+		if self.at_block.as_ref() != Some(block) {
+			return None;
+		}
+		self.ids.get(address).cloned()
+	}
+
+	/// Remember the result of looking up an address at the given block.
+	fn insert(&mut self, block: &BlockId, address: Address, id: Option<AccountId>) {
+		if self.at_block.as_ref() != Some(block) {
+			self.ids.clear();
+			self.at_block = Some(*block);
+		}
+		self.ids.insert(address, id);
+	}
+
+	/// Forget the account ID of the given address.
+	fn remove(&mut self, address: &Address) {
+		self.ids.remove(address);
+	}
+
+	/// Forget everything if it was looked up at a block given by number.
+	fn forget_numbered(&mut self) {
+		if is_numbered(&self.at_block) {
+			*self = AddressCache::default();
+		}
+	}
+}
+
+fn is_numbered(block: &Option<BlockId>) -> bool {
+	match *block {
+		Some(generic::BlockId::Number(_)) => true,
+		_ => false,
+	}
+}
+
+/// Transactions failing verification on retry.
+#[derive(Debug, Default)]
+struct Quarantine {
+	failures: HashMap<Hash, usize>,
+	held: HashSet<Hash>,
+	// earliest time each transaction backing off may be retried.
+	retry_at: HashMap<Hash, Instant>,
+}
+
+/// Readiness evaluator noting the transactions found stale, i.e. about to be culled.
+struct RecordStale<'a, R> {
+	ready: R,
+	// hash and address of each stale transaction.
+	stale: &'a mut Vec<(Hash, Address)>,
+}
+
+impl<'a, R: txpool::Ready<VerifiedTransaction>> txpool::Ready<VerifiedTransaction> for RecordStale<'a, R> {
+	fn is_ready(&mut self, xt: &VerifiedTransaction) -> Readiness {
+		let readiness = self.ready.is_ready(xt);
+		if let Readiness::Stale = readiness {
+			self.stale.push((xt.hash, xt.original.extrinsic.signed.clone()));
+		}
+		readiness
+	}
+}
+
+/// The pool wrapped by `TransactionPool`.
+type InnerPool = Pool<UncheckedExtrinsic, Hash, Verifier, Scoring, Error>;
+
+/// Read-only view of the pool's contents, as given to a `PreInsertHook`.
+pub trait PoolView {
+	/// Whether the transaction with given hash is in the pool, ready or not.
+	fn contains(&self, hash: &Hash) -> bool;
+
+	/// Get the pooled transactions sent by `who`, ordered by index.
+	fn transactions_from(&self, who: &AccountId) -> Vec<Arc<VerifiedTransaction>>;
+
+	/// Get the number of transactions in the pool, their senders and memory usage.
+	fn light_status(&self) -> LightStatus;
+}
+
+/// Hook deciding whether a verified transaction may enter the pool, given the
+/// pool's current contents.
+pub type PreInsertHook = Box<Fn(&VerifiedTransaction, &PoolView) -> bool + Send + Sync>;
+
+/// The polkadot transaction pool.
+///
+/// Wraps a `extrinsic_pool::Pool`.
+pub struct TransactionPool<A: PolkadotApi> {
+	inner: InnerPool,
+	// a copy of the inner pool's verifier, for screening transactions up front.
+	verifier: Verifier,
+	scoring: Scoring,
+	api: Arc<A>,
+	verification_cache: Mutex<VerificationCache>,
+	address_cache: Mutex<AddressCache>,
+	index_fetches: Option<Arc<IndexFetches>>,
+	options: Options,
+	// insertion id of the last transaction retried in the current sweep.
+	retry_cursor: Mutex<Option<usize>>,
+	quarantine: Mutex<Quarantine>,
+	ready_deadlines: Mutex<HashMap<Hash, BlockNumber>>,
+	reaping_pending: Mutex<HashSet<AccountId>>,
+	// peers each transaction was broadcast to.
+	broadcast_to: Mutex<HashMap<Hash, HashSet<String>>>,
+	pre_insert_hook: Option<PreInsertHook>,
+	event_sinks: Mutex<Vec<mpsc::UnboundedSender<PoolEvent>>>,
+	// transactions last known to be future, to report when they become ready.
+	known_future: Mutex<HashSet<Hash>>,
+	// hashes of pooled transactions by resolved sender, `None` for those not resolved
+	// when imported. Entries of removed transactions may linger until the next cull.
+	by_sender: Mutex<HashMap<Option<AccountId>, HashSet<Hash>>>,
+	// bumped whenever the contents of the pool change. `AtomicU64` isn't stable yet.
+	generation: AtomicUsize,
+	watchers: Arc<Watchers>,
+	metrics: PoolMetrics,
+	last_validated_block: Mutex<Option<BlockId>>,
+	best_hash: Mutex<Option<Hash>>,
+	// the block given by hash checked last, reused while imports keep targeting it.
+	checked_block: Mutex<Option<A::CheckedBlockId>>,
+	// senders whose transactions are rejected, shared with the verifier.
+	banned: Arc<RwLock<HashSet<AccountId>>>,
+	// indexed addresses whose account `fetch_senders` is fetching.
+	fetching_senders: Mutex<HashSet<AccountIndex>>,
+}
+
+// an indexed address being fetched by `fetch_senders`, no longer considered in flight
+// once dropped, whether the fetch completed or not.
+struct FetchingSender<A: PolkadotApi> {
+	pool: Arc<TransactionPool<A>>,
+	index: AccountIndex,
+}
+
+impl<A: PolkadotApi> Drop for FetchingSender<A> {
+	fn drop(&mut self) {
+		self.pool.fetching_senders.lock().remove(&self.index);
+	}
+}
+
+impl<A> TransactionPool<A> where
+	A: PolkadotApi,
+{
+	/// Create a new transaction pool.
+	pub fn new(options: Options, api: Arc<A>) -> Self {
+		let scoring = Scoring::new(options.scoring_mode, options.transaction_base_fee, options.transaction_byte_fee);
+		Self::with_scoring(options, api, scoring)
+	}
+
+	/// Create a new transaction pool prioritising transactions with the given scoring,
+	/// e.g. one using `Scoring::with_priority`.
+	///
+	/// The scoring mode and fees in `options` are ignored in favour of the scoring's.
+	pub fn with_scoring(options: Options, api: Arc<A>, scoring: Scoring) -> Self {
+		let scoring = scoring.with_allow_replace(options.allow_replace);
+		let banned = Arc::new(RwLock::new(HashSet::new()));
+		let verifier = Verifier { max_transaction_size: options.max_transaction_size, banned: banned.clone() };
+		let mut limits = options.limits.clone();
+		// room for one more transaction of a sender, and one more as counted by `mem_usage`,
+		// so that the transactions making room for a new one are only evicted once it is in.
+		limits.max_per_sender = limits.max_per_sender.saturating_add(1);
+		limits.max_mem_usage = limits.max_mem_usage.saturating_add(max_mem_usage(options.max_transaction_size));
+		if options.reject_future_when_full {
+			// likewise for the future transaction making room for a ready one.
+			limits.max_count = limits.max_count.saturating_add(1);
+		}
+		TransactionPool {
+			inner: Pool::new(limits, verifier.clone(), scoring.clone()),
+			verifier,
+			scoring,
+			api,
+			verification_cache: Mutex::new(VerificationCache::new(options.verification_cache_size)),
+			address_cache: Mutex::new(AddressCache::default()),
+			index_fetches: if options.share_index_fetches { Some(Default::default()) } else { None },
+			retry_cursor: Mutex::new(None),
+			quarantine: Mutex::new(Quarantine::default()),
+			ready_deadlines: Mutex::new(HashMap::new()),
+			reaping_pending: Mutex::new(HashSet::new()),
+			broadcast_to: Mutex::new(HashMap::new()),
+			pre_insert_hook: None,
+			event_sinks: Mutex::new(Vec::new()),
+			known_future: Mutex::new(HashSet::new()),
+			by_sender: Mutex::new(HashMap::new()),
+			generation: AtomicUsize::new(0),
+			watchers: Default::default(),
+			metrics: PoolMetrics::default(),
+			last_validated_block: Mutex::new(None),
+			best_hash: Mutex::new(None),
+			checked_block: Mutex::new(None),
+			banned,
+			fetching_senders: Mutex::new(HashSet::new()),
+			options,
+		}
+	}
+
+	/// Return a stream of the changes to the pool's contents.
+	///
+	/// Events are buffered until read; dropping the receiver unsubscribes.
+	pub fn subscribe(&self) -> mpsc::UnboundedReceiver<PoolEvent> {
+		let (sink, stream) = mpsc::unbounded();
+		self.event_sinks.lock().push(sink);
+		stream
+	}
+
+	/// Watch the state of the transaction with given hash, which may also be imported
+	/// afterwards.
+	///
+	/// Whether the transaction is ready is reported as of the block of the latest import
+	/// or `revalidate_at`.
+	pub fn watch(&self, hash: Hash) -> Watcher {
+		let (sink, receiver) = mpsc::unbounded();
+		let id = NEXT_WATCHER_ID.fetch_add(1, AtomicOrdering::Relaxed);
+		self.watchers.lock().entry(hash).or_insert_with(Watched::default).sinks.push((id, sink));
+		Watcher { hash, id, receiver, watchers: Arc::downgrade(&self.watchers) }
+	}
+
+	/// Reject any further transactions from `who`.
+	///
+	/// Transactions already in the pool are left alone, except for those whose sender is
+	/// only found to be `who` when `retry_verification` resolves it.
+	pub fn ban_sender(&self, who: AccountId) {
+		self.banned.write().insert(who);
+	}
+
+	/// Accept transactions from `who` again.
+	pub fn unban_sender(&self, who: &AccountId) {
+		self.banned.write().remove(who);
+	}
+
+	/// Whether transactions from `who` are rejected.
+	pub fn is_banned(&self, who: &AccountId) -> bool {
+		self.banned.read().contains(who)
+	}
+
+	/// Get the full status of the pool, with readiness judged by `ready`.
+	pub fn status<R: txpool::Ready<VerifiedTransaction>>(&self, ready: R) -> Status {
+		self.inner.status(ready)
+	}
+
+	/// Get how often and how long callers had to wait for the pool's lock.
+	pub fn lock_contention_stats(&self) -> ContentionStats {
+		self.inner.lock_contention_stats()
+	}
+
+	/// Get a counter which advances whenever transactions are imported, replaced, removed,
+	/// culled or have their sender resolved, so that anything computed from the pool can
+	/// tell whether it's still up to date. Queries leave it as it is.
+	pub fn generation(&self) -> u64 {
+		self.generation.load(AtomicOrdering::SeqCst) as u64
+	}
+
+	/// Get the number of transactions in the pool, their senders and memory usage.
+	pub fn light_status(&self) -> LightStatus {
+		self.inner.light_status()
+	}
+
+	/// Return a stream of the transactions imported into the pool.
+	pub fn import_notification_stream(&self) -> mpsc::UnboundedReceiver<Weak<VerifiedTransaction>> {
+		self.inner.import_notification_stream()
+	}
+
+	/// Get the counts of the pool's operations so far.
+	pub fn metrics(&self) -> PoolMetricsSnapshot {
+		self.metrics.snapshot()
+	}
+
+	/// Get the scoring used to prioritise transactions.
+	pub fn scoring(&self) -> &Scoring {
+		&self.scoring
+	}
+
+	/// Get the options the pool was created with.
+	pub fn options(&self) -> &Options {
+		&self.options
+	}
+
+	/// Get the remaining capacity of the pool in each of its limits.
+	pub fn headroom(&self) -> Headroom {
+		let limits = &self.options.limits;
+		let status = self.inner.light_status();
+
+		let busiest_sender = self.sender_histogram().values().cloned().max().unwrap_or(0);
+
+		let count = limits.max_count.saturating_sub(status.transaction_count);
+		let mem_usage = limits.max_mem_usage.saturating_sub(status.mem_usage);
+		let per_sender = limits.max_per_sender.saturating_sub(busiest_sender);
+
+		let fraction = |left: usize, limit: usize| if limit == 0 { 0.0 } else { left as f64 / limit as f64 };
+		let mut binding = (Constraint::Count, fraction(count, limits.max_count));
+		for &(constraint, left) in &[
+			(Constraint::MemUsage, fraction(mem_usage, limits.max_mem_usage)),
+			(Constraint::PerSender, fraction(per_sender, limits.max_per_sender)),
+		] {
+			if left < binding.1 {
+				binding = (constraint, left);
+			}
+		}
+
+		Headroom { count, mem_usage, per_sender, binding: binding.0 }
+	}
+
+	/// Measure waits for the pool's lock, as reported by `lock_contention_stats`, with the
+	/// given clock.
+	pub fn with_lock_clock(mut self, clock: Clock) -> Self {
+		self.inner = self.inner.with_clock(clock);
+		self
+	}
+
+	/// Veto transactions after verification, before they enter the pool, whenever
+	/// `hook` returns `false`.
+	pub fn with_pre_insert_hook(mut self, hook: PreInsertHook) -> Self {
+		self.pre_insert_hook = Some(hook);
+		self
+	}
+
+	/// Create a readiness evaluator at the given block, sharing index fetches with
+	/// the other evaluators created by the pool if configured to.
+	pub fn ready_at(&self, at: A::CheckedBlockId) -> Ready<A> {
+		let mut ready = Ready::create(at, &*self.api);
+		if let Some(ref fetches) = self.index_fetches {
+			ready = ready.with_index_fetches(fetches.clone());
+		}
+		if let Some(max) = self.options.max_nonce_ahead {
+			ready = ready.with_max_nonce_ahead(max);
+		}
+		ready
+	}
+
+	/// Verify a transaction against the state at the given block and import it.
+	///
+	/// Verification outcomes are cached, so a transaction seen recently at the same
+	/// block isn't checked again.
+	pub fn import_unchecked_extrinsic(&self, block: BlockId, uxt: UncheckedExtrinsic) -> Result<Arc<VerifiedTransaction>> {
+		let at_block = self.check_id(block)?;
+		let xt = self.verify_at(&at_block, uxt)?;
+		self.insert(xt, at_block)
+	}
+
+	/// Decode a transaction from its full encoding, as checked by `decode_extrinsic`, and
+	/// import it like `import_unchecked_extrinsic`.
+	pub fn import_from_bytes(&self, block: BlockId, raw: &[u8]) -> Result<Arc<VerifiedTransaction>> {
+		let uxt = decode_extrinsic(raw)?;
+		self.import_unchecked_extrinsic(block, uxt)
+	}
+
+	/// Import a transaction like `import_unchecked_extrinsic`, yielding the pooled
+	/// transaction rather than an error if it's already in the pool.
+	pub fn import_unchecked_extrinsic_idempotent(&self, block: BlockId, uxt: UncheckedExtrinsic) -> Result<Arc<VerifiedTransaction>> {
+		self.import_unchecked_extrinsic(block, uxt).or_else(|e| {
+			let existing = match *e.kind() {
+				ErrorKind::AlreadyImported(ref hash) => self.inner.find(hash),
+				_ => None,
+			};
+			existing.ok_or(e)
+		})
+	}
+
+	/// Submit transactions in the form they're gossiped in, like `ExtrinsicPool::submit`.
+	///
+	/// Rather than stopping at the first failure, every transaction is submitted and
+	/// the outcome of each is returned, in order. Like any other import, each is verified
+	/// at the block last noted by `note_best_block`, failing if there is none.
+	pub fn submit_each(&self, xts: Vec<FutureProofUncheckedExtrinsic>) -> Vec<Result<Hash>> {
+		xts.into_iter()
+			.map(|xt| self.submit_one(&xt))
+			.collect()
+	}
+
+	/// Verify a transaction and judge whether it would be ready, future or stale at the
+	/// given block, following the ready transactions already in the pool, without
+	/// importing it.
+	///
+	/// Neither the pool nor its caches are changed.
+	pub fn dry_run(&self, block: BlockId, uxt: UncheckedExtrinsic) -> Result<DryRunResult> {
+		let at_block = self.api.check_id(block)?;
+		let xt = VerifiedTransaction::create_with(uxt, self.options.max_transaction_size, |_, uxt| {
+			let sender = match uxt.extrinsic.signed.clone() {
+				RawAddress::Id(id) => Some(id),
+				address => self.api.lookup(&at_block, address).ok().and_then(|id| id),
+			};
+			match sender {
+				Some(id) => uxt.check(move |_| Ok(id)),
+				None => Err(UNAVAILABLE_MESSAGE),
+			}
+		})?;
+
+		// step past the pooled ready transactions first, so that one following them
+		// is judged ready. Unverified ones are judged as copies, which may be polished
+		// without the pooled transaction being changed.
+		let mut ready = self.ready_at(at_block);
+		for pooled in self.all_transactions() {
+			if pooled.is_really_verified() {
+				txpool::Ready::is_ready(&mut ready, &pooled);
+			} else {
+				txpool::Ready::is_ready(&mut ready, &(*pooled).clone());
+			}
+		}
+		let readiness = match txpool::Ready::is_ready(&mut ready, &xt) {
+			Readiness::Ready => TransactionState::Ready,
+			Readiness::Future => TransactionState::Future,
+			Readiness::Stale => TransactionState::Stale,
+		};
+
+		Ok(DryRunResult {
+			hash: xt.hash,
+			readiness,
+			sender: xt.sender().ok(),
+		})
+	}
+
+	/// Import a batch of transactions at the same block, like `import_unchecked_extrinsic`
+	/// but checking the block only once.
+	///
+	/// Yields the outcome of importing each transaction, in order.
+	pub fn import_many(&self, block: BlockId, uxts: Vec<UncheckedExtrinsic>) -> Result<Vec<Result<Arc<VerifiedTransaction>>>> {
+		let at_block = self.check_id(block)?;
+		Ok(uxts.into_iter()
+			.map(|uxt| {
+				let xt = self.verify_at(&at_block, uxt)?;
+				self.insert(xt, at_block.clone())
+			})
+			.collect())
+	}
+
+	/// Import an already verified transaction, subject to the same limits as
+	/// `import_unchecked_extrinsic`.
+	///
+	/// Only meant for tests; see `VerifiedTransaction::for_test`.
+	#[cfg(any(test, feature = "test-helpers"))]
+	pub fn import_verified(&self, block: BlockId, xt: VerifiedTransaction) -> Result<Arc<VerifiedTransaction>> {
+		let at_block = self.check_id(block)?;
+		self.insert(xt, at_block)
+	}
+
+	/// Screen a transaction for obvious forgeries before enqueueing it, without any
+	/// state lookup; see `Verifier::quick_screen`.
+	pub fn quick_screen(&self, uxt: &UncheckedExtrinsic) -> Result<()> {
+		self.verifier.quick_screen(uxt)
+	}
+
+	/// Import a transaction checking only what doesn't need chain state: that it is
+	/// signed and, if the sender is given by id, that the signature matches.
+	///
+	/// Meant for light clients relaying transactions; the sender's index and any
+	/// indexed address are left to the full nodes including the transaction.
+	pub fn import_unverified(&self, uxt: UncheckedExtrinsic) -> Result<Arc<VerifiedTransaction>> {
+		PoolMetrics::note(&self.metrics.submitted, 1);
+		let xt = VerifiedTransaction::create(uxt, self.options.max_transaction_size)
+			.and_then(|xt| reject_banned(xt, &self.banned));
+		let xt = self.record_rejection(None, xt)?;
+		let hash = xt.hash;
+		let imported = self.admit(&xt).and_then(|_| self.import_admitted(xt));
+		self.record_rejection(Some(&hash), imported.map(|outcome| outcome.imported))
+	}
+
+	/// Import a transaction like `import_unchecked_extrinsic`, also reporting the
+	/// transaction it replaced when `Options::allow_replace` is set.
+	pub fn import_with_outcome(&self, block: BlockId, uxt: UncheckedExtrinsic) -> Result<ImportOutcome> {
+		let at_block = self.check_id(block)?;
+		let xt = self.verify_at(&at_block, uxt)?;
+		self.insert_with_outcome(xt, at_block)
+	}
+
+	/// Import a transaction like `import_unchecked_extrinsic`, marking it as targeting
+	/// the runtime with given spec version.
+	///
+	/// Around a runtime upgrade, this keeps transactions for the old and new runtime apart.
+	pub fn import_for_spec(&self, block: BlockId, uxt: UncheckedExtrinsic, spec: u32) -> Result<Arc<VerifiedTransaction>> {
+		let at_block = self.check_id(block)?;
+		let mut xt = self.verify_at(&at_block, uxt)?;
+		xt.spec_version = Some(spec);
+		self.insert(xt, at_block)
+	}
+
+	/// Import a transaction like `import_unchecked_extrinsic`, valid only up to and
+	/// including block number `valid_till`. Afterwards it is considered stale.
+	pub fn import_mortal(&self, block: BlockId, uxt: UncheckedExtrinsic, valid_till: BlockNumber) -> Result<Arc<VerifiedTransaction>> {
+		let at_block = self.check_id(block)?;
+		let mut xt = self.verify_at(&at_block, uxt)?;
+		xt.valid_till = Some(valid_till);
+		self.insert(xt, at_block)
+	}
+
+	/// Get the transactions ready at the given block which target the runtime with
+	/// given spec version.
+	pub fn transactions_for_spec(&self, spec: u32, at: BlockId) -> Result<Vec<Arc<VerifiedTransaction>>> {
+		let ready = self.ready_at(self.check_id(at)?);
+		Ok(self.inner.pending(ready, |pending| pending
+			.filter(|xt| xt.spec_version == Some(spec))
+			.collect()
+		))
+	}
+
+	/// Get the transactions ready at the given block, which needn't be the best one, in
+	/// the order they would be included. Nothing is culled.
+	pub fn pending_at(&self, at: BlockId) -> Result<Vec<Arc<VerifiedTransaction>>> {
+		let ready = self.ready_at(self.check_id(at)?);
+		Ok(self.inner.pending(ready, |pending| pending.collect()))
+	}
+
+	/// Remove every transaction for which `f` returns `false`, in a single pass over the pool.
+	///
+	/// `f` is called without holding the pool's lock, so it may query the pool. Returns the
+	/// number of removed transactions.
+	pub fn retain<F>(&self, f: F) -> usize where
+		F: FnMut(&Arc<VerifiedTransaction>) -> bool,
+	{
+		let removed = self.inner.retain(f);
+		let count = removed.len();
+		if count > 0 {
+			self.bump_generation();
+		}
+		self.unindex(&removed);
+		self.forget_tracked(&removed.iter().map(|xt| xt.hash).collect::<Vec<_>>());
+		for xt in removed {
+			self.notify_watchers(&xt.hash, WatchEvent::Dropped);
+		}
+		count
+	}
+
+	/// Estimate how many blocks it will take for a ready transaction to be included, given
+	/// how many transactions fit into a block. `0` means the next block.
+	///
+	/// Returns `None` if the transaction isn't ready at the given block.
+	pub fn estimated_blocks_until_inclusion(&self, hash: &Hash, at: BlockId, block_capacity: usize) -> Option<u32> {
+		if block_capacity == 0 {
+			return None;
+		}
+
+		let ready = self.ready_at(self.check_id(at).ok()?);
+		let rank = self.inner.pending(ready, |pending| pending.position(|xt| xt.hash() == hash))?;
+		Some((rank / block_capacity) as u32)
+	}
+
+	/// Get every transaction in the pool, ready or not, without culling anything.
+	///
+	/// Transactions are ordered by sender, then index; those whose sender hasn't been
+	/// resolved yet come first.
+	pub fn all_transactions(&self) -> Vec<Arc<VerifiedTransaction>> {
+		let mut xts = self.snapshot();
+		xts.sort_by_key(|xt| (xt.sender().ok(), xt.index()));
+		xts
+	}
+
+	// get every pooled transaction, ready or not, those of each address in index order.
+	fn snapshot(&self) -> Vec<Arc<VerifiedTransaction>> {
+		let all = |_: &VerifiedTransaction| Readiness::Ready;
+		self.inner.pending(all, |pending| pending.collect())
+	}
+
+	// split a snapshot of the pool into the transactions ready as judged by `ready`, in
+	// the order `pending` yields them, and the others. judging them in a pass of its own
+	// only relies on each address's transactions coming in index order.
+	fn partition_ready<R>(&self, mut ready: R) -> (Vec<Arc<VerifiedTransaction>>, Vec<Arc<VerifiedTransaction>>) where
+		R: txpool::Ready<VerifiedTransaction>,
+	{
+		let (mut is_ready, mut not_ready) = (Vec::new(), Vec::new());
+		for xt in self.snapshot() {
+			match txpool::Ready::is_ready(&mut ready, &xt) {
+				Readiness::Ready => is_ready.push(xt),
+				Readiness::Future | Readiness::Stale => not_ready.push(xt),
+			}
+		}
+		(is_ready, not_ready)
+	}
+
+	/// Note that the indexed address `index` refers to `who` at the given block, as fetched
+	/// from elsewhere, so that `retry_verification` at that block doesn't look it up.
+	pub fn note_fetched_sender(&self, block: BlockId, index: AccountIndex, who: AccountId) {
+		self.address_cache.lock().insert(&block, RawAddress::Index(index), Some(who));
+	}
+
+	/// Fetch the senders of the unverified transactions from indexed addresses with
+	/// `fetcher`, promoting the transactions of each address as soon as its sender is
+	/// fetched. Transactions of other addresses and their retry state are left alone.
+	///
+	/// Addresses whose sender is still being fetched by an earlier call aren't fetched
+	/// again, so this can be called on every new best block.
+	///
+	/// Resolves to the number of promoted transactions.
+	pub fn fetch_senders<F: SenderFetcher>(pool: Arc<Self>, fetcher: &F, block: BlockId) -> Box<Future<Item=usize, Error=()>> where
+		A: 'static,
+	{
+		let indices = {
+			let unverified = pool.snapshot().into_iter()
+				.filter(|xt| !xt.is_really_verified())
+				.filter_map(|xt| match xt.original.extrinsic.signed {
+					RawAddress::Index(index) => Some(index),
+					RawAddress::Id(_) => None,
+				})
+				.collect::<HashSet<_>>();
+
+			let mut fetching = pool.fetching_senders.lock();
+			let indices = unverified.into_iter().filter(|index| !fetching.contains(index)).collect::<Vec<_>>();
+			fetching.extend(indices.iter().cloned());
+			indices
+		};
+
+		let fetches = indices.into_iter().map(|index| {
+			let fetching = FetchingSender { pool: pool.clone(), index };
+			fetcher.fetch_sender(block, index).then(move |fetched| -> result::Result<usize, ()> {
+				let pool = fetching.pool.clone();
+				drop(fetching);
+				let who = match fetched {
+					Ok(Some(who)) => who,
+					Ok(None) => {
+						debug!(target: "transaction-pool", "No account behind index {} at {:?}", index, block);
+						return Ok(0);
+					}
+					Err(e) => {
+						debug!(target: "transaction-pool", "Failed to fetch the account behind index {}: {}", index, e);
+						return Ok(0);
+					}
+				};
+
+				pool.note_fetched_sender(block, index, who);
+				Ok(pool.promote_fetched(index, who).len())
+			})
+		}).collect::<Vec<_>>();
+
+		Box::new(future::join_all(fetches).map(|promoted| promoted.into_iter().sum()))
+	}
+
+	// verify the unverified transactions from the indexed address `index` as sent by `who`,
+	// dropping those whose signature doesn't match or whose sender is banned. Returns the
+	// hashes of the promoted transactions.
+	fn promote_fetched(&self, index: AccountIndex, who: AccountId) -> Vec<Hash> {
+		let address = RawAddress::Index(index);
+		let unverified = self.snapshot().into_iter()
+			.filter(|xt| !xt.is_really_verified() && xt.original.extrinsic.signed == address)
+			.collect::<Vec<_>>();
+
+		let mut promoted = Vec::new();
+		let mut dropped = Vec::new();
+		for xt in unverified {
+			if let Err(e) = xt.polish(move |_| Ok(who)) {
+				debug!(target: "transaction-pool", "Dropping {}, failing verification as sent by {}: {}", xt.hash, who, e);
+				dropped.push(xt.hash);
+			} else if self.is_banned(&who) {
+				debug!(target: "transaction-pool", "Dropping {} from a banned sender", xt.hash);
+				dropped.push(xt.hash);
+			} else {
+				promoted.push(xt.hash);
+			}
+		}
+
+		{
+			let mut quarantine = self.quarantine.lock();
+			for hash in &promoted {
+				quarantine.failures.remove(hash);
+				quarantine.retry_at.remove(hash);
+				quarantine.held.remove(hash);
+			}
+		}
+		if !promoted.is_empty() {
+			self.bump_generation();
+		}
+		for hash in &promoted {
+			self.notify(PoolEvent::Promoted(*hash));
+		}
+		self.remove_and_forget(&dropped, true);
+		promoted
+	}
+
+	/// Get the transactions ready as judged by `ready` in an order depending on nothing but
+	/// the transactions themselves, so that authorities with identical pools build
+	/// identical blocks.
+	///
+	/// Senders take turns in the order of their account IDs, each contributing its ready
+	/// transaction with the lowest index on its turn.
+	pub fn ready_ordered_for_authoring<R>(&self, ready: R) -> Vec<Arc<VerifiedTransaction>> where
+		R: txpool::Ready<VerifiedTransaction>,
+	{
+		let mut by_sender = BTreeMap::new();
+		for xt in self.inner.pending(ready, |pending| pending.collect::<Vec<_>>()) {
+			by_sender.entry(xt.sender().ok()).or_insert_with(Vec::new).push(xt);
+		}
+
+		let mut queues = by_sender.into_iter()
+			.map(|(_, mut xts)| {
+				xts.sort_by_key(|xt| (xt.index(), xt.hash));
+				xts.into_iter().collect::<VecDeque<_>>()
+			})
+			.collect::<Vec<_>>();
+
+		let mut ordered = Vec::new();
+		while !queues.is_empty() {
+			for queue in &mut queues {
+				ordered.extend(queue.pop_front());
+			}
+			queues.retain(|queue| !queue.is_empty());
+		}
+		ordered
+	}
+
+	/// Get the transactions to include in a block built on top of `at`: those ready
+	/// there, best first, up to `max_encoded` bytes in total.
+	///
+	/// Readiness, ordering and the size budget are evaluated in a single pass with the
+	/// pool locked, so the batch is consistent. The batch ends at the first transaction
+	/// exceeding the budget, as skipping it could leave its successors out of order.
+	pub fn propose_batch(&self, at: BlockId, max_encoded: usize) -> Result<Vec<Arc<VerifiedTransaction>>> {
+		let ready = self.ready_at(self.check_id(at)?).with_evaluate_as_of(EvaluateAsOf::NextBlock);
+		Ok(self.inner.pending(ready, |pending| {
+			let mut size = 0;
+			pending.take_while(|xt| {
+				size += xt.encoded_size();
+				size <= max_encoded
+			}).collect()
+		}))
+	}
+
+	/// Note that transactions were broadcast to the given peers.
+	pub fn on_broadcasted(&self, propagated: HashMap<Hash, Vec<String>>) {
+		{
+			let mut broadcast_to = self.broadcast_to.lock();
+			for (hash, peers) in &propagated {
+				// not remembered for transactions which left the pool meanwhile.
+				if self.inner.contains(hash) {
+					broadcast_to.entry(*hash).or_insert_with(HashSet::new).extend(peers.iter().cloned());
+				}
+			}
+
+			// forget about transactions which left the pool since.
+			broadcast_to.retain(|hash, _| self.inner.contains(hash));
+		}
+		for (hash, peers) in &propagated {
+			self.notify_watchers(hash, WatchEvent::Broadcast(peers.clone()));
+		}
+		self.inner.on_broadcasted(propagated)
+	}
+
+	/// Get those of the `candidates` peers the transaction hasn't been broadcast to yet.
+	pub fn peers_to_broadcast(&self, hash: &Hash, candidates: &[String]) -> Vec<String> {
+		match self.broadcast_to.lock().get(hash) {
+			Some(served) => candidates.iter().filter(|peer| !served.contains(*peer)).cloned().collect(),
+			None => candidates.to_vec(),
+		}
+	}
+
+	/// Export the encoded transactions which are ready or future at the given block,
+	/// leaving out stale ones.
+	///
+	/// The exported transactions can be imported again with `decode_extrinsic`.
+	pub fn export_valid_at(&self, at: BlockId) -> Result<Vec<Vec<u8>>> {
+		let ready = self.ready_at(self.check_id(at)?);
+		let ready_hashes = self.inner.pending(ready.clone(), |pending| pending.map(|xt| xt.hash).collect::<HashSet<_>>());
+
+		Ok(self.all_transactions()
+			.into_iter()
+			.filter(|xt| ready_hashes.contains(&xt.hash) || {
+				let mut ready = ready.clone();
+				match txpool::Ready::is_ready(&mut ready, &**xt) {
+					Readiness::Stale => false,
+					Readiness::Future | Readiness::Ready => true,
+				}
+			})
+			.map(|xt| xt.encoded().to_vec())
+			.collect())
+	}
+
+	/// Write every transaction in the pool to `writer`, ready or not, e.g. to keep them
+	/// across a restart of the node.
+	///
+	/// Each transaction is written in its full encoding, so that `load` can skip any
+	/// which can't be decoded any more.
+	pub fn persist<W: Write>(&self, mut writer: W) -> Result<()> {
+		let encoded = self.all_transactions()
+			.into_iter()
+			.map(|xt| xt.encoded().to_vec())
+			.collect::<Vec<_>>();
+		writer.write_all(&encoded.encode())?;
+		Ok(())
+	}
+
+	/// Import the transactions written by `persist` at the given block.
+	///
+	/// Transactions which fail to import are skipped. Returns the number of restored
+	/// transactions.
+	pub fn load<R: Read>(&self, block: BlockId, mut reader: R) -> Result<usize> {
+		let mut data = Vec::new();
+		reader.read_to_end(&mut data)?;
+		let encoded: Vec<Vec<u8>> = Slicable::decode(&mut &data[..]).ok_or(ErrorKind::InvalidExtrinsicFormat)?;
+
+		let mut restored = 0;
+		for raw in encoded {
+			match self.import_from_bytes(block, &raw) {
+				Ok(_) => restored += 1,
+				Err(e) => debug!(target: "transaction-pool", "Not restoring transaction: {}", e),
+			}
+		}
+		debug!(target: "transaction-pool", "Restored {} transactions", restored);
+		Ok(restored)
+	}
+
+	/// Get the pooled transactions sent by `who`, ordered by index.
+	///
+	/// Transactions whose sender hasn't been resolved yet aren't included.
+	pub fn transactions_from(&self, who: &AccountId) -> Vec<Arc<VerifiedTransaction>> {
+		let mut xts = self.snapshot().into_iter()
+			.filter(|xt| xt.sender().ok().as_ref() == Some(who))
+			.collect::<Vec<_>>();
+		xts.sort_by_key(|xt| xt.index());
+		xts
+	}
+
+	/// Get the transactions of `sender` which are ready, in index order.
+	pub fn pending_for_sender<R>(&self, sender: AccountId, ready: R) -> Vec<Arc<VerifiedTransaction>> where
+		R: txpool::Ready<VerifiedTransaction>,
+	{
+		let mut xts = self.inner.pending(ready, |pending| pending
+			.filter(|xt| xt.sender().ok() == Some(sender))
+			.collect::<Vec<_>>()
+		);
+		xts.sort_by(|a, b| txpool::Scoring::compare(&self.scoring, &**a, &**b));
+		xts
+	}
+
+	/// Count the transactions which are ready as judged by `ready`, and those which aren't,
+	/// without collecting them.
+	///
+	/// Stale transactions are counted along with the future ones until they are culled.
+	pub fn ready_future_counts<R>(&self, ready: R) -> (usize, usize) where
+		R: txpool::Ready<VerifiedTransaction>,
+	{
+		let status = self.inner.status(ready);
+		(status.ready, status.future + status.stalled)
+	}
+
+	/// Get the fee payable for including the given transaction.
+	pub fn fee_of(&self, xt: &VerifiedTransaction) -> Balance {
+		xt.fee(self.options.transaction_base_fee, self.options.transaction_byte_fee)
+	}
+
+	/// Whether the transaction with given hash is in the pool, ready or not.
+	///
+	/// Nothing is culled or verified, unlike `transaction_status`, and the pool isn't scanned.
+	pub fn contains(&self, hash: &Hash) -> bool {
+		self.inner.contains(hash)
+	}
+
+	/// Get the state of the transaction with given hash, as judged by `ready`.
+	pub fn transaction_status<R>(&self, hash: &Hash, ready: R) -> TransactionState where
+		R: txpool::Ready<VerifiedTransaction> + Clone,
+	{
+		let xt = match self.inner.find(hash) {
+			Some(xt) => xt,
+			None => return TransactionState::Unknown,
+		};
+
+		if self.inner.pending(ready.clone(), |mut pending| pending.any(|p| p.hash == xt.hash)) {
+			return TransactionState::Ready;
+		}
+
+		// not reachable through the ready ones; only stale if it is on its own.
+		let mut ready = ready;
+		match <R as txpool::Ready<VerifiedTransaction>>::is_ready(&mut ready, &xt) {
+			Readiness::Stale => TransactionState::Stale,
+			Readiness::Future | Readiness::Ready => TransactionState::Future,
+		}
+	}
+
+	/// Get the total fees payable by the transactions of each sender in the pool.
+	///
+	/// Transactions whose sender hasn't been resolved yet are totalled under `None`.
+	/// Totals saturate at `u64::max_value()`.
+	pub fn fee_by_sender(&self) -> HashMap<Option<AccountId>, u64> {
+		let mut fees = HashMap::new();
+		for xt in self.snapshot() {
+			let fee = self.fee_of(&xt);
+			let fee = if fee > u64::max_value() as Balance { u64::max_value() } else { fee as u64 };
+			let total = fees.entry(xt.sender().ok()).or_insert(0u64);
+			*total = total.saturating_add(fee);
+		}
+		fees
+	}
+
+	/// Get the number of transactions of each sender in the pool.
+	///
+	/// Transactions whose sender hasn't been resolved yet are counted under `None`.
+	pub fn sender_histogram(&self) -> HashMap<Option<AccountId>, usize> {
+		let mut counts = HashMap::new();
+		for xt in self.snapshot() {
+			*counts.entry(xt.sender().ok()).or_insert(0) += 1;
+		}
+		counts
+	}
+
+	/// Get the indices for which a sender has more than one transaction in the pool,
+	/// along with the hashes of those transactions in the order they were created.
+	///
+	/// Transactions whose sender hasn't been resolved yet are left out.
+	pub fn nonce_conflicts(&self) -> Vec<(AccountId, Index, Vec<Hash>)> {
+		let mut xts = self.all_transactions();
+		xts.sort_by_key(|xt| xt.insertion_id);
+
+		let mut by_nonce = HashMap::new();
+		for xt in xts {
+			if let Ok(sender) = xt.sender() {
+				by_nonce.entry((sender, xt.index())).or_insert_with(Vec::new).push(xt.hash);
+			}
+		}
+
+		let mut conflicts = by_nonce.into_iter()
+			.filter(|&(_, ref hashes)| hashes.len() > 1)
+			.map(|((sender, index), hashes)| (sender, index, hashes))
+			.collect::<Vec<_>>();
+		conflicts.sort_by_key(|&(sender, index, _)| (sender, index));
+		conflicts
+	}
+
+	/// Get everything the pool knows about the account `who` at the given block.
+	pub fn account_overview(&self, who: AccountId, at: BlockId) -> Result<AccountOverview> {
+		let at_block = self.check_id(at)?;
+		let next_index = self.api.index(&at_block, who)?;
+		let ready = self.inner.pending(self.ready_at(at_block), |pending| pending
+			.filter(|xt| xt.sender().ok() == Some(who))
+			.map(|xt| xt.hash)
+			.collect::<Vec<_>>()
+		);
+
+		let xts = self.transactions_from(&who);
+		let future = xts.iter()
+			.filter(|xt| xt.index() >= next_index && !ready.contains(&xt.hash))
+			.map(|xt| xt.hash)
+			.collect();
+		let gaps = match xts.last() {
+			Some(last) if last.index() > next_index =>
+				(next_index..last.index()).filter(|i| !xts.iter().any(|xt| xt.index() == *i)).collect(),
+			_ => Vec::new(),
+		};
+		let queued_fees = xts.iter()
+			.filter(|xt| xt.index() >= next_index)
+			.map(|xt| self.fee_of(xt))
+			.sum();
+
+		Ok(AccountOverview { next_index, ready, future, gaps, queued_fees })
+	}
+
+	/// Import a transaction like `import_unchecked_extrinsic`, dropping it again if it
+	/// hasn't become ready by block number `ready_by`.
+	///
+	/// Deadlines are enforced by `drop_missed_ready_deadlines`.
+	pub fn import_with_ready_deadline(&self, block: BlockId, uxt: UncheckedExtrinsic, ready_by: BlockNumber) -> Result<Arc<VerifiedTransaction>> {
+		let xt = self.import_unchecked_extrinsic(block, uxt)?;
+		self.ready_deadlines.lock().insert(xt.hash, ready_by);
+		Ok(xt)
+	}
+
+	/// Drop the transactions imported longer than `Options::ttl` ago. Their watchers
+	/// are notified.
+	///
+	/// Returns the hashes of the dropped transactions.
+	pub fn remove_expired(&self) -> Vec<Hash> {
+		self.remove_expired_at(Instant::now())
+	}
+
+	fn remove_expired_at(&self, now: Instant) -> Vec<Hash> {
+		let ttl = match self.options.ttl {
+			Some(ttl) => ttl,
+			None => return Vec::new(),
+		};
+
+		let expired = self.all_transactions()
+			.into_iter()
+			.filter(|xt| now.duration_since(xt.imported_at) > ttl)
+			.map(|xt| xt.hash)
+			.collect::<Vec<_>>();
+
+		if !expired.is_empty() {
+			debug!(target: "transaction-pool", "Dropping {} transactions older than {:?}", expired.len(), ttl);
+			self.evict(&expired);
+		}
+		expired
+	}
+
+	/// Drop transactions which aren't ready at block `at`, numbered `number`, even though
+	/// they were required to be. Their watchers are notified.
+	///
+	/// Returns the number of dropped transactions.
+	pub fn drop_missed_ready_deadlines(&self, at: BlockId, number: BlockNumber) -> Result<usize> {
+		let due = {
+			let mut deadlines = self.ready_deadlines.lock();
+			let due = deadlines.iter()
+				.filter(|&(_, ready_by)| *ready_by <= number)
+				.map(|(hash, _)| *hash)
+				.collect::<Vec<_>>();
+			for hash in &due {
+				deadlines.remove(hash);
+			}
+			due
+		};
+
+		if due.is_empty() {
+			return Ok(0);
+		}
+
+		let ready = self.ready_at(self.check_id(at)?);
+		let ready = self.inner.pending(ready, |pending| pending.map(|xt| xt.hash).collect::<HashSet<_>>());
+		let missed = due.into_iter().filter(|hash| !ready.contains(hash)).collect::<Vec<_>>();
+
+		for hash in &missed {
+			debug!(target: "transaction-pool", "Dropping {}: not ready by its deadline", hash);
+		}
+		Ok(self.evict(&missed))
+	}
+
+	/// Drop all transactions from the pool, notifying their watchers, and forget everything
+	/// remembered about them.
+	///
+	/// Transactions imported concurrently may survive.
+	pub fn clear(&self) {
+		let hashes = self.all_transactions().into_iter().map(|xt| xt.hash).collect::<Vec<_>>();
+		self.evict(&hashes);
+
+		*self.verification_cache.lock() = VerificationCache::new(self.options.verification_cache_size);
+		*self.address_cache.lock() = AddressCache::default();
+		*self.retry_cursor.lock() = None;
+		*self.quarantine.lock() = Quarantine::default();
+		self.ready_deadlines.lock().clear();
+		self.broadcast_to.lock().clear();
+
+		debug!(target: "transaction-pool", "Cleared {} transactions", hashes.len());
+		for hash in hashes {
+			self.notify(PoolEvent::Removed(hash));
+		}
+	}
+
+	/// Remove transactions which turned out to be invalid, e.g. because they failed to
+	/// apply during block building. Returns the removed transactions.
+	///
+	/// Anything remembered about verifying them, including the accounts their addresses
+	/// resolved to, is forgotten.
+	pub fn remove_invalid(&self, hashes: &[Hash]) -> Vec<Arc<VerifiedTransaction>> {
+		let removed = self.remove_and_forget(hashes, true);
+		PoolMetrics::note(&self.metrics.removed, removed.len());
+		removed
+	}
+
+	/// Remove the transactions with given hashes, which were included in an imported block.
+	///
+	/// Returns the removed transactions. Hashes of transactions not in the pool are ignored.
+	/// As with `remove_invalid`, anything remembered about verifying them is forgotten.
+	pub fn prune_included(&self, hashes: &[Hash]) -> Vec<Arc<VerifiedTransaction>> {
+		let removed = self.inner.remove_included(hashes);
+		self.forget_removed(hashes, removed, false)
+	}
+
+	// remove transactions along with what was cached about them, so that a transaction
+	// or address coming back is looked at afresh.
+	fn remove_and_forget(&self, hashes: &[Hash], is_invalid: bool) -> Vec<Arc<VerifiedTransaction>> {
+		let removed = self.inner.remove(hashes, is_invalid);
+		self.forget_removed(hashes, removed, is_invalid)
+	}
+
+	fn forget_removed(&self, hashes: &[Hash], removed: Vec<Option<Arc<VerifiedTransaction>>>, is_invalid: bool) -> Vec<Arc<VerifiedTransaction>> {
+		let removed = removed
+			.into_iter()
+			.filter_map(|xt| xt)
+			.collect::<Vec<_>>();
+		if !removed.is_empty() {
+			self.bump_generation();
+		}
+		self.unindex(&removed);
+		self.forget_tracked(hashes);
+
+		{
+			let mut verification_cache = self.verification_cache.lock();
+			let mut address_cache = self.address_cache.lock();
+			for xt in &removed {
+				verification_cache.remove(&xt.hash);
+				address_cache.remove(&xt.original.extrinsic.signed);
+			}
+		}
+		for xt in &removed {
+			self.notify(PoolEvent::Removed(xt.hash));
+			self.notify_watchers(&xt.hash, if is_invalid { WatchEvent::Invalid } else { WatchEvent::Dropped });
+		}
+		removed
+	}
+
+	/// Cull stale transactions from the pool, only considering those of `senders` if given.
+	///
+	/// Each sender's transactions are evaluated in index order within the same pass, so
+	/// culling stale ones leaves the next index ready at once, even if several stale
+	/// transactions shared an index.
+	///
+	/// What was cached about the culled transactions and their senders' addresses is
+	/// forgotten, so that follow-up transactions are judged against fresh chain state.
+	///
+	/// Returns the number of culled transactions.
+	pub fn cull<R>(&self, senders: Option<&[Address]>, ready: R) -> usize where
+		R: txpool::Ready<VerifiedTransaction>,
+	{
+		let mut stale = Vec::new();
+		let culled = self.inner.cull(senders, RecordStale { ready, stale: &mut stale });
+		PoolMetrics::note(&self.metrics.culled, culled);
+		if culled > 0 {
+			self.bump_generation();
+		}
+		self.prune_by_sender();
+		self.forget_tracked(&stale.iter().map(|&(hash, _)| hash).collect::<Vec<_>>());
+
+		{
+			let mut verification_cache = self.verification_cache.lock();
+			let mut address_cache = self.address_cache.lock();
+			for &(ref hash, ref address) in &stale {
+				verification_cache.remove(hash);
+				address_cache.remove(address);
+			}
+		}
+		for (hash, _) in stale {
+			self.notify(PoolEvent::Culled(hash));
+			self.notify_watchers(&hash, WatchEvent::Dropped);
+		}
+		culled
+	}
+
+	/// Cull stale transactions from the pool and then compute the pending set.
+	pub fn cull_and_get_pending<R, F, T>(&self, ready: R, f: F) -> T where
+		R: txpool::Ready<VerifiedTransaction> + Clone,
+		F: FnOnce(txpool::PendingIterator<VerifiedTransaction, R, Scoring, extrinsic_pool::Listener<Hash, VerifiedTransaction>>) -> T,
+	{
+		self.cull(None, ready.clone());
+		self.note_became_ready(ready.clone());
+		self.inner.pending(ready, f)
+	}
+
+	/// Flag the account `who` as about to be reaped, e.g. because its balance is about to
+	/// drop below the existential deposit.
+	///
+	/// Once `note_account_reaped` reports the reaping, its transactions which aren't ready
+	/// are dropped rather than left waiting for an index the account will never reach.
+	pub fn mark_reaping_pending(&self, who: AccountId) {
+		self.reaping_pending.lock().insert(who);
+	}
+
+	/// Note that the account `who` was reaped in block `at`.
+	///
+	/// If the account was flagged by `mark_reaping_pending`, its transactions which aren't
+	/// ready at `at` are dropped and their watchers notified. Returns the number of
+	/// dropped transactions.
+	pub fn note_account_reaped(&self, who: AccountId, at: BlockId) -> Result<usize> {
+		if !self.reaping_pending.lock().remove(&who) {
+			return Ok(0);
+		}
+
+		let ready = self.ready_at(self.check_id(at)?);
+		let ready = self.inner.pending(ready, |pending| pending.map(|xt| xt.hash).collect::<HashSet<_>>());
+		let stuck = self.transactions_from(&who)
+			.into_iter()
+			.map(|xt| xt.hash)
+			.filter(|hash| !ready.contains(hash))
+			.collect::<Vec<_>>();
+
+		debug!(target: "transaction-pool", "Dropping {} transactions of reaped account {}", stuck.len(), who);
+		Ok(self.evict(&stuck))
+	}
+
+	/// Re-evaluate the whole pool at the given block, e.g. a new best block.
+	///
+	/// Senders which couldn't be resolved before are retried, subject to `retry_verification`'s
+	/// limits, then stale transactions are culled and the rest is tallied.
+	pub fn revalidate_at(&self, block: BlockId) -> Result<RevalidationReport> {
+		let promoted = if self.needs_reverification().is_empty() {
+			0
+		} else {
+			self.retry_verification(block)?.promoted.len()
+		};
+
+		let at_block = self.check_id(block)?;
+		let stale = self.cull(None, self.ready_at(at_block.clone()));
+		let (ready, future) = self.ready_future_counts(self.ready_at(at_block.clone()));
+		self.note_became_ready(self.ready_at(at_block.clone()));
+		self.update_all_watched(at_block);
+		*self.last_validated_block.lock() = Some(block);
+		Ok(RevalidationReport { ready, future, stale, promoted })
+	}
+
+	/// Note the hash of the new best block.
+	///
+	/// What the pool remembers about blocks is kept per block hash where it was given
+	/// one. What it remembers about blocks given by number is forgotten whenever the
+	/// best block changes, since the number may now refer to a block on another fork.
+	pub fn note_best_block(&self, hash: Hash) {
+		{
+			let mut best_hash = self.best_hash.lock();
+			if *best_hash == Some(hash) {
+				return;
+			}
+			*best_hash = Some(hash);
+		}
+
+		self.verification_cache.lock().forget_numbered();
+		self.address_cache.lock().forget_numbered();
+	}
+
+	/// Get the block the pool was last revalidated at with `revalidate_at`, if any.
+	///
+	/// Revalidating at the same block again is only needed if transactions were imported
+	/// in between.
+	pub fn last_validated_block(&self) -> Option<BlockId> {
+		*self.last_validated_block.lock()
+	}
+
+	/// Get the hashes of the transactions whose sender couldn't be resolved yet, quarantined
+	/// ones included.
+	///
+	/// These are judged `Future` until verified, like those waiting for an earlier index;
+	/// `retry_verification` only needs calling if there are any.
+	pub fn needs_reverification(&self) -> Vec<Hash> {
+		self.all_transactions()
+			.into_iter()
+			.filter(|xt| !xt.is_really_verified())
+			.map(|xt| xt.hash)
+			.collect()
+	}
+
+	/// Retry verification of transactions whose sender couldn't be resolved yet, e.g.
+	/// because the sending account has only been created by the given block.
+	///
+	/// At most `max_retries_per_call` transactions are processed, oldest first; the next
+	/// call carries on with the following ones. Transactions which still fail verification
+	/// are left as they are, backing off for `Options::retry_backoff` and dropped after
+	/// `Options::drop_unverified_after` failures.
+	pub fn retry_verification(&self, block: BlockId) -> Result<RetriedVerification> {
+		self.retry_verification_at(block, Instant::now())
+	}
+
+	fn retry_verification_at(&self, block: BlockId, now: Instant) -> Result<RetriedVerification> {
+		let at_block = self.check_id(block)?;
+		let (promoted, dropped, more_remaining) = self.retry_batch_at(&at_block, block, now);
+		if !promoted.is_empty() {
+			self.bump_generation();
+		}
+		// removing takes the quarantine lock, so the batch must have released it.
+		self.remove_and_forget(&dropped, true);
+		Ok(RetriedVerification { promoted, more_remaining, dropped })
+	}
+
+	// retry verifying the next batch of unresolved transactions, returning the promoted
+	// and the ones to drop, and whether any are left for the next call.
+	fn retry_batch_at(&self, at_block: &A::CheckedBlockId, block: BlockId, now: Instant) -> (Vec<Hash>, Vec<Hash>, bool) {
+		let mut cursor = self.retry_cursor.lock();
+		let mut quarantine = self.quarantine.lock();
+
+		let mut unverified = self.snapshot().into_iter()
+			.filter(|xt| !xt.is_really_verified() && cursor.map_or(true, |c| xt.insertion_id > c))
+			.filter(|xt| !quarantine.held.contains(&xt.hash))
+			.filter(|xt| quarantine.retry_at.get(&xt.hash).map_or(true, |at| *at <= now))
+			.collect::<Vec<_>>();
+		unverified.sort_by_key(|xt| xt.insertion_id);
+
+		let more_remaining = unverified.len() > self.options.max_retries_per_call;
+		unverified.truncate(self.options.max_retries_per_call);
+
+		let mut promoted = Vec::new();
+		let mut dropped = Vec::new();
+		for xt in unverified {
+			*cursor = Some(xt.insertion_id);
+			let sender = self.lookup_at(at_block, xt.original.extrinsic.signed.clone());
+			let verified = match sender {
+				Some(id) => match xt.polish(move |_| Ok(id)) {
+					Ok(()) => true,
+					Err(e) => {
+						debug!(target: "transaction-pool", "Retried verification of {} failed: {}", xt.hash, e);
+						false
+					}
+				},
+				None => {
+					trace!(target: "transaction-pool", "Sender of {} is still unknown at {:?}", xt.hash, block);
+					false
+				}
+			};
+
+			if verified && xt.sender().map_or(false, |sender| self.is_banned(&sender)) {
+				debug!(target: "transaction-pool", "Dropping {} from a banned sender", xt.hash);
+				dropped.push(xt.hash);
+				continue;
+			}
+
+			if verified {
+				quarantine.failures.remove(&xt.hash);
+				quarantine.retry_at.remove(&xt.hash);
+				self.notify(PoolEvent::Promoted(xt.hash));
+				promoted.push(xt.hash);
+				continue;
+			}
+
+			let failures = {
+				let failures = quarantine.failures.entry(xt.hash).or_insert(0);
+				*failures += 1;
+				*failures
+			};
+			if self.options.drop_unverified_after.map_or(false, |max| failures >= max) {
+				debug!(target: "transaction-pool", "Dropping {} after {} failed retries", xt.hash, failures);
+				dropped.push(xt.hash);
+			} else if self.options.quarantine_after.map_or(false, |max| failures >= max) {
+				debug!(target: "transaction-pool", "Quarantining {} after {} failed retries", xt.hash, failures);
+				quarantine.failures.remove(&xt.hash);
+				quarantine.retry_at.remove(&xt.hash);
+				quarantine.held.insert(xt.hash);
+			} else if let Some(backoff) = self.options.retry_backoff {
+				let delay = backoff * (1u32 << cmp::min(failures - 1, 16));
+				trace!(target: "transaction-pool", "Retrying {} in {:?}", xt.hash, delay);
+				quarantine.retry_at.insert(xt.hash, now + delay);
+			}
+		}
+
+		if !more_remaining {
+			*cursor = None;
+		}
+		(promoted, dropped, more_remaining)
+	}
+
+	/// Get the hashes of the transactions excluded from `retry_verification` after
+	/// failing it too often.
+	pub fn quarantined(&self) -> Vec<Hash> {
+		self.quarantine.lock().held.iter().cloned().collect()
+	}
+
+	/// Let the given quarantined transactions be retried again.
+	pub fn release_quarantined(&self, hashes: &[Hash]) {
+		let mut quarantine = self.quarantine.lock();
+		for hash in hashes {
+			quarantine.held.remove(hash);
+		}
+	}
+
+	/// Take a snapshot of the pool, classifying its transactions at the given block.
+	pub fn freeze(&self, at: BlockId) -> Result<FrozenPool> {
+		let (ready, future) = self.partition_ready(self.ready_at(self.check_id(at)?));
+		Ok(FrozenPool { at, ready, future })
+	}
+
+	/// Check that the transaction with given hash still hashes to it.
+	///
+	/// Returns `None` if the transaction isn't in the pool.
+	pub fn verify_hash_integrity(&self, hash: &Hash) -> Option<bool> {
+		self.inner.find(hash).map(|xt| xt.has_intact_hash())
+	}
+
+	/// Check the internal consistency of the pool, panicking on violation.
+	///
+	/// Only meant for tests.
+	#[cfg(any(test, feature = "test-helpers"))]
+	pub fn assert_invariants(&self) {
+		for xt in self.snapshot() {
+			assert!(xt.has_intact_hash(), "Transaction {} does not hash to its stored hash", xt.hash());
+		}
+	}
+
+	fn insert(&self, xt: VerifiedTransaction, at_block: A::CheckedBlockId) -> Result<Arc<VerifiedTransaction>> {
+		self.insert_with_outcome(xt, at_block).map(|outcome| outcome.imported)
+	}
+
+	fn insert_with_outcome(&self, xt: VerifiedTransaction, at_block: A::CheckedBlockId) -> Result<ImportOutcome> {
+		let hash = xt.hash;
+		let outcome = self.record_rejection(Some(&hash), self.admit_and_import(xt, at_block.clone()))?;
+		// only the sender's transactions may have changed state.
+		let states = self.sender_readiness(&outcome.imported, at_block);
+		if states.iter().any(|&(other, is_ready)| other == hash && !is_ready) {
+			self.known_future.lock().insert(hash);
+		}
+		self.update_watched(states);
+		Ok(outcome)
+	}
+
+	fn admit_and_import(&self, xt: VerifiedTransaction, at_block: A::CheckedBlockId) -> Result<ImportOutcome> {
+		self.admit(&xt)?;
+		self.check_nonce_gap(&xt, &at_block)?;
+		// victims are only picked here, and evicted once `xt` is in, so that nothing is
+		// evicted for a transaction which then fails to be imported.
+		let sender_victim = self.sender_to_evict(&xt)?;
+		let future_victim = self.future_to_evict(&xt, at_block.clone(), sender_victim.as_ref().map(|victim| &**victim))?;
+		let mem_victims = {
+			let evicting = sender_victim.iter().chain(future_victim.iter()).map(|victim| &**victim).collect::<Vec<_>>();
+			self.mem_to_evict(&xt, at_block, &evicting)?
+		};
+		let outcome = self.import_admitted(xt)?;
+		if let Some(victim) = sender_victim {
+			debug!(target: "transaction-pool", "Evicting {} in favour of {}", victim.hash, outcome.imported.hash);
+			self.evict(&[victim.hash]);
+		}
+		if let Some(victim) = future_victim {
+			debug!(target: "transaction-pool", "Evicting future {} in favour of {}", victim.hash, outcome.imported.hash);
+			self.evict(&[victim.hash]);
+		}
+		if !mem_victims.is_empty() {
+			debug!(target: "transaction-pool", "Evicting {} transactions to make room for {}", mem_victims.len(), outcome.imported.hash);
+			self.evict(&mem_victims);
+		}
+		Ok(outcome)
+	}
+
+	/// Count and log a failure to import the transaction with given hash, if known.
+	fn record_rejection<T>(&self, hash: Option<&Hash>, result: Result<T>) -> Result<T> {
+		if let Err(ref e) = result {
+			match *e.kind() {
+				ErrorKind::AlreadyImported(_) => {
+					trace!(target: "transaction-pool", "{}", e);
+					PoolMetrics::note(&self.metrics.already_imported, 1);
+				}
+				_ => {
+					log_rejection(hash, e);
+					PoolMetrics::note(&self.metrics.rejected, 1);
+				}
+			}
+		}
+		result
+	}
+
+	/// Run the checks for inserting `xt` which don't depend on chain state.
+	fn admit(&self, xt: &VerifiedTransaction) -> Result<()> {
+		// checked first, so that a duplicate can't evict anything to make room. One imported
+		// concurrently is still told apart by the inner pool in `import_admitted`.
+		if self.inner.contains(&xt.hash) {
+			bail!(ErrorKind::AlreadyImported(xt.hash));
+		}
+
+		if let Some(ref hook) = self.pre_insert_hook {
+			if !hook(xt, self) {
+				bail!(ErrorKind::VetoedByHook(xt.hash));
+			}
+		}
+		Ok(())
+	}
+
+	/// Make sure the index of `xt` is within `max_nonce_gap` of its sender's on-chain
+	/// index at `at_block`, if the sender is known.
+	fn check_nonce_gap(&self, xt: &VerifiedTransaction, at_block: &A::CheckedBlockId) -> Result<()> {
+		let (max, sender) = match (self.options.max_nonce_gap, xt.sender()) {
+			(Some(max), Ok(sender)) => (max, sender),
+			_ => return Ok(()),
+		};
+
+		let on_chain = self.api.index(at_block, sender)?;
+		if xt.index() > on_chain.saturating_add(max) {
+			bail!(ErrorKind::NonceTooFarAhead(xt.index(), on_chain));
+		}
+		Ok(())
+	}
+
+	fn import_admitted(&self, xt: VerifiedTransaction) -> Result<ImportOutcome> {
+		let hash = xt.hash;
+		let (imported, replaced) = match self.inner.import_replacing(xt) {
+			Ok(outcome) => outcome,
+			Err(e) => match *e.kind() {
+				ErrorKind::Pool(txpool::ErrorKind::AlreadyImported(_)) => bail!(ErrorKind::AlreadyImported(hash)),
+				_ => return Err(e),
+			},
+		};
+		self.bump_generation();
+		self.by_sender.lock()
+			.entry(imported.sender().ok())
+			.or_insert_with(HashSet::new)
+			.insert(imported.hash);
+		if let Some(ref replaced) = replaced {
+			self.unindex(&[replaced.clone()]);
+			self.forget_tracked(&[replaced.hash]);
+			PoolMetrics::note(&self.metrics.replaced, 1);
+			self.notify_watchers(&replaced.hash, WatchEvent::Dropped);
+		}
+		self.notify(PoolEvent::Imported(imported.hash));
+		Ok(ImportOutcome { imported, replaced })
+	}
+
+	fn bump_generation(&self) {
+		self.generation.fetch_add(1, AtomicOrdering::SeqCst);
+	}
+
+	fn notify(&self, event: PoolEvent) {
+		self.event_sinks.lock()
+			.retain(|sink| sink.unbounded_send(event.clone()).is_ok());
+	}
+
+	// tell subscribers about the transactions last known to be future which are ready
+	// as judged by `ready`.
+	fn note_became_ready<R>(&self, ready: R) where
+		R: txpool::Ready<VerifiedTransaction>,
+	{
+		let became_ready = {
+			let mut known_future = self.known_future.lock();
+			if known_future.is_empty() {
+				return;
+			}
+
+			let became_ready = self.inner.pending(ready, |pending| pending
+				.filter(|xt| known_future.contains(&xt.hash))
+				.map(|xt| xt.hash)
+				.collect::<Vec<_>>()
+			);
+			for hash in &became_ready {
+				known_future.remove(hash);
+			}
+			known_future.retain(|hash| self.inner.contains(hash));
+			became_ready
+		};
+
+		for hash in became_ready {
+			self.notify(PoolEvent::BecameReady(hash));
+		}
+	}
+
+	// send `event` to the watchers of the transaction, ending their streams if it
+	// left the pool.
+	fn notify_watchers(&self, hash: &Hash, event: WatchEvent) {
+		let mut watchers = self.watchers.lock();
+		let gone = match event {
+			WatchEvent::Dropped | WatchEvent::Invalid => true,
+			_ => false,
+		};
+		let unwatched = match watchers.get_mut(hash) {
+			Some(watched) => {
+				watched.sinks.retain(|&(_, ref sink)| sink.unbounded_send(event.clone()).is_ok());
+				gone || watched.sinks.is_empty()
+			}
+			None => return,
+		};
+		if unwatched {
+			watchers.remove(hash);
+		}
+	}
+
+	// get the pooled transactions sent from the address of `xt`, in index order.
+	fn pooled_from_sender_of(&self, xt: &VerifiedTransaction) -> Vec<Arc<VerifiedTransaction>> {
+		let mut from_sender = self.pooled_from(xt.sender().ok());
+		if !xt.is_really_verified() {
+			from_sender.retain(|other| other.original.extrinsic.signed == xt.original.extrinsic.signed);
+		}
+		from_sender.sort_by_key(|other| (other.index(), other.insertion_id));
+		from_sender
+	}
+
+	// judge which of the pooled transactions sent from the address of `xt` are ready at
+	// `at_block`, sparing a pass over the whole pool.
+	fn sender_readiness(&self, xt: &VerifiedTransaction, at_block: A::CheckedBlockId) -> Vec<(Hash, bool)> {
+		let mut ready = self.ready_at(at_block);
+		self.pooled_from_sender_of(xt).into_iter()
+			.map(|other| match txpool::Ready::is_ready(&mut ready, &other) {
+				Readiness::Ready => (other.hash, true),
+				_ => (other.hash, false),
+			})
+			.collect()
+	}
+
+	// report watched transactions which became ready or future at the given block.
+	fn update_all_watched(&self, at_block: A::CheckedBlockId) {
+		let watched = self.watchers.lock().keys().cloned().collect::<Vec<_>>();
+		if watched.is_empty() {
+			return;
+		}
+
+		let ready = self.inner.pending(self.ready_at(at_block), |pending| pending.map(|xt| xt.hash).collect::<HashSet<_>>());
+		let states = watched.into_iter()
+			.filter(|hash| self.inner.contains(hash))
+			.map(|hash| {
+				let is_ready = ready.contains(&hash);
+				(hash, is_ready)
+			})
+			.collect::<Vec<_>>();
+		self.update_watched(states);
+	}
+
+	// report watched transactions which became ready or future, given the hashes of
+	// pooled transactions and whether each is ready.
+	fn update_watched(&self, states: Vec<(Hash, bool)>) {
+		for (hash, is_ready) in states {
+			let state = if is_ready { TransactionState::Ready } else { TransactionState::Future };
+			let changed = {
+				let mut watchers = self.watchers.lock();
+				match watchers.get_mut(&hash) {
+					Some(watched) => {
+						let changed = watched.state != Some(state);
+						watched.state = Some(state);
+						changed
+					}
+					None => false,
+				}
+			};
+			if changed {
+				let event = match state {
+					TransactionState::Ready => WatchEvent::Ready,
+					_ => WatchEvent::Future,
+				};
+				self.notify_watchers(&hash, event);
+			}
+		}
+	}
+
+	// evict transactions from the pool, telling their watchers.
+	fn evict(&self, hashes: &[Hash]) -> usize {
+		let watched = {
+			let watchers = self.watchers.lock();
+			hashes.iter()
+				.filter(|hash| watchers.contains_key(*hash))
+				.cloned()
+				.collect::<Vec<_>>()
+		};
+		let watched = watched.into_iter().filter(|hash| self.inner.contains(hash)).collect::<Vec<_>>();
+		let pooled = hashes.iter().filter_map(|hash| self.inner.find(hash)).collect::<Vec<_>>();
+
+		let evicted = self.inner.evict(hashes);
+		if evicted > 0 {
+			self.bump_generation();
+		}
+		self.unindex(&pooled);
+		self.forget_tracked(hashes);
+		for hash in watched {
+			self.notify_watchers(&hash, WatchEvent::Dropped);
+		}
+		evicted
+	}
+
+	// get the pooled transactions of `sender`, first filing the unresolved transactions
+	// polished since being imported under their sender.
+	fn pooled_from(&self, sender: Option<AccountId>) -> Vec<Arc<VerifiedTransaction>> {
+		let mut by_sender = self.by_sender.lock();
+		for hash in by_sender.remove(&None).unwrap_or_default() {
+			if let Some(xt) = self.inner.find(&hash) {
+				by_sender.entry(xt.sender().ok()).or_insert_with(HashSet::new).insert(hash);
+			}
+		}
+
+		let mut pooled = Vec::new();
+		if let Some(hashes) = by_sender.get_mut(&sender) {
+			hashes.retain(|hash| match self.inner.find(hash) {
+				Some(xt) => {
+					pooled.push(xt);
+					true
+				}
+				None => false,
+			});
+		}
+		if pooled.is_empty() {
+			by_sender.remove(&sender);
+		}
+		pooled
+	}
+
+	// drop removed transactions from the per-sender index.
+	fn unindex(&self, removed: &[Arc<VerifiedTransaction>]) {
+		let mut by_sender = self.by_sender.lock();
+		for xt in removed {
+			// polished since being imported, it may still be filed as unresolved.
+			for sender in &[xt.sender().ok(), None] {
+				let emptied = match by_sender.get_mut(sender) {
+					Some(hashes) => {
+						hashes.remove(&xt.hash);
+						hashes.is_empty()
+					}
+					None => false,
+				};
+				if emptied {
+					by_sender.remove(sender);
+				}
+			}
+		}
+	}
+
+	// forget the ready deadlines and retry backoff of transactions no longer in the pool.
+	// neither the retry cursor nor the quarantine may be locked by the caller.
+	fn forget_tracked(&self, hashes: &[Hash]) {
+		if hashes.is_empty() {
+			return;
+		}
+
+		let mut quarantine = self.quarantine.lock();
+		let mut deadlines = self.ready_deadlines.lock();
+		for hash in hashes {
+			quarantine.failures.remove(hash);
+			quarantine.retry_at.remove(hash);
+			quarantine.held.remove(hash);
+			deadlines.remove(hash);
+		}
+	}
+
+	// drop transactions no longer in the pool from the per-sender index.
+	fn prune_by_sender(&self) {
+		self.by_sender.lock().retain(|_, hashes| {
+			hashes.retain(|hash| self.inner.contains(hash));
+			!hashes.is_empty()
+		});
+	}
+
+	/// Make sure the sender of `xt` stays within `limits.max_per_sender`, picking its
+	/// lowest-priority transaction to evict once `xt` is in if `xt` should replace it.
+	///
+	/// The inner pool has room for one more transaction per sender, so nothing is evicted
+	/// for a transaction which then fails to be imported.
+	fn sender_to_evict(&self, xt: &VerifiedTransaction) -> Result<Option<Arc<VerifiedTransaction>>> {
+		let sender = xt.sender().ok();
+		let from_sender = self.pooled_from(sender);
+		if from_sender.len() < self.options.limits.max_per_sender {
+			return Ok(None);
+		}
+
+		// the transaction furthest in the future is the least likely to be included soon.
+		let lowest = from_sender.into_iter().max_by_key(|other| (other.index(), other.insertion_id));
+		match lowest {
+			Some(lowest) if <Scoring as txpool::Scoring<VerifiedTransaction>>::should_replace(&self.scoring, &lowest, xt) => Ok(Some(lowest)),
+			_ => bail!(ErrorKind::TooManyFromSender(sender)),
+		}
+	}
+
+	/// Get the priority of keeping `xt` in the pool when making room, given whether it
+	/// is a future transaction.
+	fn retention(&self, xt: &VerifiedTransaction, is_future: bool) -> f64 {
+		let score = self.scoring.score(xt) as f64;
+		match self.options.future_half_life {
+			Some(half_life) if is_future => {
+				let as_secs = |d: Duration| d.as_secs() as f64 + d.subsec_nanos() as f64 * 1e-9;
+				score / 2f64.powf(as_secs(xt.imported_at.elapsed()) / as_secs(half_life))
+			}
+			_ => score,
+		}
+	}
+
+	/// With `reject_future_when_full` set and the pool full, make sure `xt` is ready at
+	/// `at_block`, and pick the lowest-priority future transaction to evict once `xt` is in,
+	/// counting `evicting` as gone already.
+	///
+	/// The inner pool has room for one more transaction with the option set, so nothing is
+	/// evicted for a transaction which then fails to be imported.
+	fn future_to_evict(&self, xt: &VerifiedTransaction, at_block: A::CheckedBlockId, evicting: Option<&VerifiedTransaction>) -> Result<Option<Arc<VerifiedTransaction>>> {
+		let is_evicting = |pooled: &VerifiedTransaction| evicting.map_or(false, |evicting| evicting.hash == pooled.hash);
+		let count = self.inner.light_status().transaction_count - evicting.map_or(0, |_| 1);
+		if !self.options.reject_future_when_full || count < self.options.limits.max_count {
+			return Ok(None);
+		}
+
+		// step past the sender's pooled transactions first, like `dry_run`.
+		let mut ready = self.ready_at(at_block.clone());
+		for pooled in self.pooled_from_sender_of(xt) {
+			if !is_evicting(&*pooled) {
+				txpool::Ready::is_ready(&mut ready, &pooled);
+			}
+		}
+		match txpool::Ready::is_ready(&mut ready, xt) {
+			Readiness::Ready => {},
+			_ => {
+				debug!(target: "transaction-pool", "Rejecting {} which isn't ready, since the pool is full", xt.hash);
+				bail!(ErrorKind::PoolFull);
+			}
+		}
+
+		let (_, future) = self.partition_ready(self.ready_at(at_block));
+		let lowest = future.into_iter()
+			.filter(|pooled| !is_evicting(&**pooled))
+			.map(|pooled| (self.retention(&pooled, true), pooled))
+			.min_by(|&(a, ref x), &(b, ref y)| a.partial_cmp(&b)
+				.unwrap_or(Ordering::Equal)
+				.then(x.insertion_id.cmp(&y.insertion_id))
+			);
+		match lowest {
+			Some((_, lowest)) => Ok(Some(lowest)),
+			None => {
+				debug!(target: "transaction-pool", "Rejecting {}, since the pool is full of ready transactions", xt.hash);
+				bail!(ErrorKind::PoolFull)
+			}
+		}
+	}
+
+	/// Pick the transactions retained with lower priority than `xt` to evict once it is in,
+	/// so that it fits into the memory limit, counting `evicting` as gone already.
+	///
+	/// The inner pool has room for one more transaction as counted by `mem_usage`, so
+	/// nothing is evicted for a transaction which then fails to be imported.
+	fn mem_to_evict(&self, xt: &VerifiedTransaction, at_block: A::CheckedBlockId, evicting: &[&VerifiedTransaction]) -> Result<Vec<Hash>> {
+		let max_mem_usage = self.options.limits.max_mem_usage;
+		let mem_usage = self.inner.light_status().mem_usage
+			.saturating_sub(evicting.iter().map(|evicting| VerifiedTransactionOps::mem_usage(*evicting)).sum::<usize>());
+		let needed = mem_usage + VerifiedTransactionOps::mem_usage(xt);
+		if needed <= max_mem_usage {
+			return Ok(Vec::new());
+		}
+
+		let retention = self.scoring.score(xt) as f64;
+		let ready = self.inner.pending(self.ready_at(at_block), |pending| pending.map(|xt| xt.hash).collect::<HashSet<_>>());
+		let mut candidates = self.snapshot().into_iter()
+			.filter(|pooled| evicting.iter().all(|evicting| evicting.hash != pooled.hash))
+			.map(|xt| (self.retention(&xt, !ready.contains(&xt.hash)), xt))
+			.filter(|&(r, _)| r < retention)
+			.collect::<Vec<_>>();
+		candidates.sort_by(|&(a, ref x), &(b, ref y)| a.partial_cmp(&b)
+			.unwrap_or(Ordering::Equal)
+			.then(x.insertion_id.cmp(&y.insertion_id))
+		);
+
+		let mut freed = 0;
+		let mut victims = Vec::new();
+		for (_, victim) in candidates.into_iter().take(self.options.max_evictions_per_import) {
+			if needed - freed <= max_mem_usage {
+				break;
+			}
+			freed += VerifiedTransactionOps::mem_usage(&*victim);
+			victims.push(victim.hash);
+		}
+
+		if needed - freed > max_mem_usage {
+			bail!(ErrorKind::PoolFull);
+		}
+		Ok(victims)
+	}
+
+	// verify and import a transaction at the best block, like any other.
+	fn submit_one(&self, xt: &FutureProofUncheckedExtrinsic) -> Result<Hash> {
+		// TODO: more general transaction pool, which can handle more kinds of vec-encoded transactions,
+		// even when runtime is out of date.
+		let xt = decode_primitive_extrinsic(xt)?;
+		let best_hash = (*self.best_hash.lock()).ok_or(ErrorKind::NoBestBlock)?;
+		self.import_unchecked_extrinsic(BlockId::hash(best_hash), xt).map(|xt| xt.hash)
+	}
+
+	/// Check the given block ID, reusing the outcome of the previous check if it was for
+	/// the same block hash.
+	///
+	/// IDs given by number are checked afresh every time, since the number may refer to
+	/// another block after a reorganisation.
+	fn check_id(&self, block: BlockId) -> Result<A::CheckedBlockId> {
+		if let generic::BlockId::Number(_) = block {
+			return Ok(self.api.check_id(block)?);
+		}
+
+		if let Some(ref checked) = *self.checked_block.lock() {
+			if *checked.block_id() == block {
+				return Ok(checked.clone());
+			}
+		}
+
+		let checked = self.api.check_id(block)?;
+		*self.checked_block.lock() = Some(checked.clone());
+		Ok(checked)
+	}
+
+	/// Look up the account ID of an address at the given block, remembering the result
+	/// for further lookups at the same block.
+	fn lookup_at(&self, at_block: &A::CheckedBlockId, address: Address) -> Option<AccountId> {
+		let block = at_block.block_id();
+		if let Some(id) = self.address_cache.lock().get(block, &address) {
+			return id;
+		}
+
+		let id = self.api.lookup(at_block, address.clone()).ok()?;
+		self.address_cache.lock().insert(block, address, id);
+		id
+	}
+
+	fn verify_at(&self, at_block: &A::CheckedBlockId, uxt: UncheckedExtrinsic) -> Result<VerifiedTransaction> {
+		let cache = &self.verification_cache;
+		let block = at_block.block_id();
+
+		PoolMetrics::note(&self.metrics.submitted, 1);
+		// spare the signature check where the sender is given.
+		if let RawAddress::Id(ref id) = uxt.extrinsic.signed {
+			if self.is_banned(id) {
+				return self.record_rejection(None, Err(ErrorKind::Banned(*id).into()));
+			}
+		}
+
+		let mut checked_hash = None;
+		let verified = VerifiedTransaction::create_with(uxt, self.options.max_transaction_size, |hash, uxt| {
+			checked_hash = Some(*hash);
+			if let Some(outcome) = cache.lock().get(block, hash) {
+				trace!(target: "transaction-pool", "Reusing verification outcome of {} at {:?}", hash, block);
+				return outcome;
+			}
+
+			let sender = match uxt.extrinsic.signed.clone() {
+				RawAddress::Id(id) => Some(id),
+				address => self.lookup_at(at_block, address),
+			};
+			let outcome = match sender {
+				Some(id) => uxt.check(move |_| Ok(id)),
+				None => Err(UNAVAILABLE_MESSAGE),
+			};
+
+			match outcome {
+				// the sender may become known later; don't remember that.
+				Err(e) if e == UNAVAILABLE_MESSAGE => {},
+				ref outcome => cache.lock().insert(block, *hash, outcome.clone()),
+			}
+			outcome
+		}).and_then(|xt| reject_banned(xt, &self.banned));
+
+		let verified = self.record_rejection(checked_hash.as_ref(), verified)?;
+		if verified.is_really_verified() {
+			PoolMetrics::note(&self.metrics.verified, 1);
+		}
+		Ok(verified)
+	}
+}
+
+impl<A: PolkadotApi> PoolView for TransactionPool<A> {
+	fn contains(&self, hash: &Hash) -> bool {
+		TransactionPool::contains(self, hash)
+	}
+
+	fn transactions_from(&self, who: &AccountId) -> Vec<Arc<VerifiedTransaction>> {
+		TransactionPool::transactions_from(self, who)
+	}
+
+	fn light_status(&self) -> LightStatus {
+		TransactionPool::light_status(self)
+	}
+}
+
+impl<A> ExtrinsicPool<FutureProofUncheckedExtrinsic, Hash> for TransactionPool<A> where
+	A: PolkadotApi + Send + Sync + 'static,
+{
+	type Error = Error;
+
+	fn submit(&self, xts: Vec<FutureProofUncheckedExtrinsic>) -> Result<Vec<Hash>> {
+		xts.into_iter()
+			.map(|xt| self.submit_one(&xt))
+			.collect()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::HashMap;
+	use std::mem;
+	use std::sync::{Arc, Barrier, atomic::{AtomicUsize, Ordering as AtomicOrdering}};
+	use std::thread;
+	use std::time::{Duration, Instant};
+	use super::{AccountOverview, Constraint, Error, ErrorKind, EvaluateAsOf, IndexFetches, Limits, TransactionState, Options, PoolEvent, PoolMetricsSnapshot, PoolView, Prioritise, RevalidationReport, TransactionPool, Ready, Scoring,
+		ScoringMode, SenderFetcher, VerifiedTransaction, VerifiedTransactionOps, WatchEvent};
+	use futures::{Future, Stream};
+	use parking_lot::Mutex;
+	use substrate_runtime_primitives::bft::{MisbehaviorKind, MisbehaviorReport};
+	use substrate_runtime_primitives::traits::{BlakeTwo256, Hashing};
+	use extrinsic_pool::txpool::Readiness;
+	use substrate_keyring::Keyring::{self, *};
+	use codec::Slicable;
+	use polkadot_api::{PolkadotApi, BlockBuilder, CheckedBlockId, Result};
+	use primitives::{AccountId, AccountIndex, Block, BlockId, Hash, Index, SessionKey, Timestamp,
+		UncheckedExtrinsic as FutureProofUncheckedExtrinsic};
+	use runtime::{RawAddress, Call, ConsensusCall, ConsensusPrivCall, CouncilCall, DemocracyCall, PrivCall, TimestampCall, BareExtrinsic, Extrinsic, UncheckedExtrinsic};
+	use primitives::parachain::{CandidateReceipt, DutyRoster, Id as ParaId};
+	use substrate_runtime_primitives::{MaybeUnsigned, generic};
+
+	struct TestBlockBuilder;
+	impl BlockBuilder for TestBlockBuilder {
+		fn push_extrinsic(&mut self, _extrinsic: FutureProofUncheckedExtrinsic) -> Result<()> { unimplemented!() }
+		fn bake(self) -> Result<Block> { unimplemented!() }
+	}
+
+	#[derive(Clone)]
+	struct TestCheckedBlockId(pub BlockId);
+	impl CheckedBlockId for TestCheckedBlockId {
+		fn block_id(&self) -> &BlockId { &self.0 }
+	}
+
+	// blocks given by hash are numbered by the first byte of it.
+	fn number_of(at: &TestCheckedBlockId) -> u32 {
+		match at.0 {
+			generic::BlockId::Number(n) => n as u32,
+			generic::BlockId::Hash(h) => h[0] as u32,
+		}
+	}
+
+	#[derive(Default)]
+	struct TestPolkadotApi {
+		check_ids: AtomicUsize,
+		lookups: AtomicUsize,
+		indexes: AtomicUsize,
+		batch_indexes: AtomicUsize,
+		// on-chain indices lowered since, e.g. by killing the account.
+		lowered_indexes: Mutex<HashMap<AccountId, Index>>,
+		// whether fetching indices fails.
+		index_fails: bool,
+	}
+
+	impl TestPolkadotApi {
+		fn check_ids(&self) -> usize {
+			self.check_ids.load(AtomicOrdering::SeqCst)
+		}
+
+		fn lookups(&self) -> usize {
+			self.lookups.load(AtomicOrdering::SeqCst)
+		}
+
+		fn indexes(&self) -> usize {
+			self.indexes.load(AtomicOrdering::SeqCst)
+		}
+
+		fn batch_indexes(&self) -> usize {
+			self.batch_indexes.load(AtomicOrdering::SeqCst)
+		}
+
+		fn lower_index(&self, who: Keyring, index: Index) {
+			self.lowered_indexes.lock().insert(who.to_raw_public().into(), index);
+		}
+
+		fn index_of(&self, at: &TestCheckedBlockId, account: &AccountId) -> Index {
+			match self.lowered_indexes.lock().get(account) {
+				Some(index) => *index,
+				None => (account[0] as u32) + number_of(at),
+			}
+		}
+	}
+	impl PolkadotApi for TestPolkadotApi {
+		type CheckedBlockId = TestCheckedBlockId;
+		type BlockBuilder = TestBlockBuilder;
+
+		fn check_id(&self, id: BlockId) -> Result<TestCheckedBlockId> {
+			self.check_ids.fetch_add(1, AtomicOrdering::SeqCst);
+			Ok(TestCheckedBlockId(id))
+		}
+		fn session_keys(&self, _at: &TestCheckedBlockId) -> Result<Vec<SessionKey>> { unimplemented!() }
+		fn validators(&self, _at: &TestCheckedBlockId) -> Result<Vec<AccountId>> { unimplemented!() }
+		fn random_seed(&self, _at: &TestCheckedBlockId) -> Result<Hash> { unimplemented!() }
+		fn duty_roster(&self, _at: &TestCheckedBlockId) -> Result<DutyRoster> { unimplemented!() }
+		fn timestamp(&self, _at: &TestCheckedBlockId) -> Result<u64> { unimplemented!() }
+		fn evaluate_block(&self, _at: &TestCheckedBlockId, _block: Block) -> Result<bool> { unimplemented!() }
+		fn active_parachains(&self, _at: &TestCheckedBlockId) -> Result<Vec<ParaId>> { unimplemented!() }
+		fn parachain_code(&self, _at: &TestCheckedBlockId, _parachain: ParaId) -> Result<Option<Vec<u8>>> { unimplemented!() }
+		fn parachain_head(&self, _at: &TestCheckedBlockId, _parachain: ParaId) -> Result<Option<Vec<u8>>> { unimplemented!() }
+		fn build_block(&self, _at: &TestCheckedBlockId, _timestamp: Timestamp, _new_heads: Vec<CandidateReceipt>) -> Result<Self::BlockBuilder> { unimplemented!() }
+		fn inherent_extrinsics(&self, _at: &TestCheckedBlockId, _timestamp: Timestamp, _new_heads: Vec<CandidateReceipt>) -> Result<Vec<Vec<u8>>> { unimplemented!() }
+
+		fn index(&self, _at: &TestCheckedBlockId, _account: AccountId) -> Result<Index> {
+			self.indexes.fetch_add(1, AtomicOrdering::SeqCst);
+			if self.index_fails {
+				return Err("index unavailable".into());
+			}
+			Ok(self.index_of(_at, &_account))
+		}
+		fn batch_index(&self, _at: &TestCheckedBlockId, _accounts: &[AccountId]) -> Result<Vec<Index>> {
+			self.batch_indexes.fetch_add(1, AtomicOrdering::SeqCst);
+			Ok(_accounts.iter().map(|a| self.index_of(_at, a)).collect())
+		}
+		fn lookup(&self, _at: &TestCheckedBlockId, _address: RawAddress<AccountId, AccountIndex>) -> Result<Option<AccountId>> {
+			self.lookups.fetch_add(1, AtomicOrdering::SeqCst);
+			match _address {
+				RawAddress::Id(i) => Ok(Some(i)),
+				RawAddress::Index(i) => Ok(match (i < 8, i + (number_of(_at) as u64) % 8) {
+					(false, _) => None,
+					(_, 0) => Some(Alice.to_raw_public().into()),
+					(_, 1) => Some(Bob.to_raw_public().into()),
+					(_, 2) => Some(Charlie.to_raw_public().into()),
+					(_, 3) => Some(Dave.to_raw_public().into()),
+					(_, 4) => Some(Eve.to_raw_public().into()),
+					(_, 5) => Some(Ferdie.to_raw_public().into()),
+					(_, 6) => Some(One.to_raw_public().into()),
+					(_, 7) => Some(Two.to_raw_public().into()),
+					_ => None,
+				}),
+			}
+		}
+	}
+
+	fn uxt(who: Keyring, nonce: Index, use_id: bool) -> UncheckedExtrinsic {
+		uxt_with_call(who, nonce, use_id, Call::Timestamp(TimestampCall::set(0)))
+	}
+
+	fn uxt_with_call(who: Keyring, nonce: Index, use_id: bool, function: Call) -> UncheckedExtrinsic {
+		let sxt = BareExtrinsic {
+			signed: who.to_raw_public().into(),
+			index: nonce,
+			function,
+		};
+		let sig = sxt.using_encoded(|e| who.sign(e));
+		UncheckedExtrinsic::new(Extrinsic {
+			signed: if use_id { RawAddress::Id(sxt.signed) } else { RawAddress::Index(
+				match who {
+					Alice => 0,
+					Bob => 1,
+					Charlie => 2,
+					Dave => 3,
+					Eve => 4,
+					Ferdie => 5,
+					One => 6,
+					Two => 7,
+				}
+			)},
+			index: sxt.index,
+			function: sxt.function,
+		}, MaybeUnsigned(sig.into())).using_encoded(|e| UncheckedExtrinsic::decode(&mut &e[..])).unwrap()
+	}
+
+	// a call that ranks above ordinary transactions, and encodes larger than them.
+	fn misbehavior_report() -> Call {
+		Call::Consensus(ConsensusCall::report_misbehavior(MisbehaviorReport {
+			parent_hash: [0; 32].into(),
+			parent_number: 0,
+			target: [1; 32].into(),
+			misbehavior: MisbehaviorKind::BftDoubleCommit(0, ([2; 32].into(), [3; 64].into()), ([4; 32].into(), [5; 64].into())),
+		}))
+	}
+
+	// import transactions checking only what doesn't need chain state, leaving indexed
+	// addresses unresolved.
+	fn import_all(pool: &TransactionPool<TestPolkadotApi>, xts: Vec<UncheckedExtrinsic>) -> Vec<Arc<VerifiedTransaction>> {
+		xts.into_iter().map(|xt| pool.import_unverified(xt).unwrap()).collect()
+	}
+
+	// a pool with block 0 noted as the best, which submitted transactions are imported at.
+	fn submitting_pool(api: &Arc<TestPolkadotApi>) -> TransactionPool<TestPolkadotApi> {
+		let pool = TransactionPool::new(Default::default(), api.clone());
+		pool.note_best_block([0; 32].into());
+		pool
+	}
+
+	// submit transactions in the form they're gossiped in, which lacks the length prefixes.
+	fn submit(pool: &TransactionPool<TestPolkadotApi>, xts: Vec<UncheckedExtrinsic>) -> super::Result<Vec<Hash>> {
+		super::ExtrinsicPool::submit(pool, xts.iter().map(|xt| xt.encode()[4..].to_vec()).collect())
+	}
+
+	#[test]
+	fn id_submission_should_work() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let pool = submitting_pool(&api);
+		submit(&pool, vec![uxt(Alice, 209, true)]).unwrap();
+
+		let ready = Ready::create(api.check_id(BlockId::number(0)).unwrap(), &*api);
+		let pending: Vec<_> = pool.cull_and_get_pending(ready, |p| p.map(|a| (a.sender().ok(), a.index())).collect());
+		assert_eq!(pending, vec![(Some(Alice.to_raw_public().into()), 209)]);
+	}
+
+	#[test]
+	fn index_submission_should_work() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let pool = submitting_pool(&api);
+		submit(&pool, vec![uxt(Alice, 209, false)]).unwrap();
+
+		let ready = Ready::create(api.check_id(BlockId::number(0)).unwrap(), &*api);
+		let pending: Vec<_> = pool.cull_and_get_pending(ready, |p| p.map(|a| (a.sender().ok(), a.index())).collect());
+		assert_eq!(pending, vec![(Some(Alice.to_raw_public().into()), 209)]);
+	}
+
+	#[test]
+	fn multiple_id_submission_should_work() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let pool = submitting_pool(&api);
+		submit(&pool, vec![uxt(Alice, 209, true)]).unwrap();
+		submit(&pool, vec![uxt(Alice, 210, true)]).unwrap();
+
+		let ready = Ready::create(api.check_id(BlockId::number(0)).unwrap(), &*api);
+		let pending: Vec<_> = pool.cull_and_get_pending(ready, |p| p.map(|a| (a.sender().ok(), a.index())).collect());
+		assert_eq!(pending, vec![(Some(Alice.to_raw_public().into()), 209), (Some(Alice.to_raw_public().into()), 210)]);
+	}
+
+	#[test]
+	fn multiple_index_submission_should_work() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let pool = submitting_pool(&api);
+		submit(&pool, vec![uxt(Alice, 209, false)]).unwrap();
+		submit(&pool, vec![uxt(Alice, 210, false)]).unwrap();
+
+		let ready = Ready::create(api.check_id(BlockId::number(0)).unwrap(), &*api);
+		let pending: Vec<_> = pool.cull_and_get_pending(ready, |p| p.map(|a| (a.sender().ok(), a.index())).collect());
+		assert_eq!(pending, vec![(Some(Alice.to_raw_public().into()), 209), (Some(Alice.to_raw_public().into()), 210)]);
+	}
+
+	#[test]
+	fn id_based_early_nonce_should_be_culled() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let pool = submitting_pool(&api);
+		submit(&pool, vec![uxt(Alice, 208, true)]).unwrap();
+
+		let ready = Ready::create(api.check_id(BlockId::number(0)).unwrap(), &*api);
+		let pending: Vec<_> = pool.cull_and_get_pending(ready, |p| p.map(|a| (a.sender().ok(), a.index())).collect());
+		assert_eq!(pending, vec![]);
+	}
+
+	#[test]
+	fn index_based_early_nonce_should_be_culled() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let pool = submitting_pool(&api);
+		submit(&pool, vec![uxt(Alice, 208, false)]).unwrap();
+
+		let ready = Ready::create(api.check_id(BlockId::number(0)).unwrap(), &*api);
+		let pending: Vec<_> = pool.cull_and_get_pending(ready, |p| p.map(|a| (a.sender().ok(), a.index())).collect());
+		assert_eq!(pending, vec![]);
+	}
+
+	#[test]
+	fn id_based_late_nonce_should_be_queued() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let pool = submitting_pool(&api);
+		let ready = || Ready::create(api.check_id(BlockId::number(0)).unwrap(), &*api);
+
+		submit(&pool, vec![uxt(Alice, 210, true)]).unwrap();
+		let pending: Vec<_> = pool.cull_and_get_pending(ready(), |p| p.map(|a| (a.sender().ok(), a.index())).collect());
+		assert_eq!(pending, vec![]);
+
+		submit(&pool, vec![uxt(Alice, 209, true)]).unwrap();
+		let pending: Vec<_> = pool.cull_and_get_pending(ready(), |p| p.map(|a| (a.sender().ok(), a.index())).collect());
+		assert_eq!(pending, vec![(Some(Alice.to_raw_public().into()), 209), (Some(Alice.to_raw_public().into()), 210)]);
+	}
+
+	#[test]
+	fn index_based_late_nonce_should_be_queued() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let pool = submitting_pool(&api);
+		let ready = || Ready::create(api.check_id(BlockId::number(0)).unwrap(), &*api);
+
+		submit(&pool, vec![uxt(Alice, 210, false)]).unwrap();
+		let pending: Vec<_> = pool.cull_and_get_pending(ready(), |p| p.map(|a| (a.sender().ok(), a.index())).collect());
+		assert_eq!(pending, vec![]);
+
+		submit(&pool, vec![uxt(Alice, 209, false)]).unwrap();
+		let pending: Vec<_> = pool.cull_and_get_pending(ready(), |p| p.map(|a| (a.sender().ok(), a.index())).collect());
+		assert_eq!(pending, vec![(Some(Alice.to_raw_public().into()), 209), (Some(Alice.to_raw_public().into()), 210)]);
+	}
+
+	#[test]
+	fn index_then_id_submission_should_make_progress() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let pool = submitting_pool(&api);
+		submit(&pool, vec![uxt(Alice, 209, false)]).unwrap();
+		submit(&pool, vec![uxt(Alice, 210, true)]).unwrap();
+
+		let ready = Ready::create(api.check_id(BlockId::number(0)).unwrap(), &*api);
+		let pending: Vec<_> = pool.cull_and_get_pending(ready, |p| p.map(|a| (a.sender().ok(), a.index())).collect());
+		assert_eq!(pending, vec![
+			(Some(Alice.to_raw_public().into()), 209)
+		]);
+	}
+
+	#[test]
+	fn id_then_index_submission_should_make_progress() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let pool = submitting_pool(&api);
+		submit(&pool, vec![uxt(Alice, 209, true)]).unwrap();
+		submit(&pool, vec![uxt(Alice, 210, false)]).unwrap();
+
+		let ready = Ready::create(api.check_id(BlockId::number(0)).unwrap(), &*api);
+		let pending: Vec<_> = pool.cull_and_get_pending(ready, |p| p.map(|a| (a.sender().ok(), a.index())).collect());
+		assert_eq!(pending, vec![
+			(Some(Alice.to_raw_public().into()), 209)
+		]);
+	}
+
+	#[test]
+	fn index_change_should_result_in_second_tx_culled_or_future() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let pool = submitting_pool(&api);
+		submit(&pool, vec![uxt(Alice, 209, false)]).unwrap();
+		submit(&pool, vec![uxt(Alice, 210, false)]).unwrap();
+
+		let ready = Ready::create(api.check_id(BlockId::number(0)).unwrap(), &*api);
+		let pending: Vec<_> = pool.cull_and_get_pending(ready, |p| p.map(|a| (a.sender().ok(), a.index())).collect());
+		assert_eq!(pending, vec![
+			(Some(Alice.to_raw_public().into()), 209),
+			(Some(Alice.to_raw_public().into()), 210)
+		]);
+
+		// first xt is mined, but that has a side-effect of switching index 0 from Alice to Bob.
+		// second xt now invalid signature, so it fails.
+
+		// there is no way of reporting this back to the queue right now (TODO). this should cause
+		// the queue to flush all information regarding the sender index/account.
+
+		// after this, a re-evaluation of the second's readiness should result in it being thrown
+		// out (or maybe placed in future queue).
+/*
+		// TODO: uncomment once the new queue design is in.
+		let ready = Ready::create(api.check_id(BlockId::number(1)).unwrap(), &*api);
+		let pending: Vec<_> = pool.cull_and_get_pending(ready, |p| p.map(|a| (a.sender().ok(), a.index())).collect());
+		assert_eq!(pending, vec![]);
+*/
+	}
+
+	#[test]
+	fn verification_outcome_is_reused_at_the_same_block() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let pool = TransactionPool::new(Default::default(), api.clone());
+
+		let xt = pool.import_unchecked_extrinsic(BlockId::number(0), uxt(Alice, 209, false)).unwrap();
+		assert!(xt.is_really_verified());
+		assert_eq!(api.lookups(), 1);
+
+		// cull it and have it re-gossiped.
+		pool.retain(|pooled| pooled.hash() != xt.hash());
+		let xt = pool.import_unchecked_extrinsic(BlockId::number(0), uxt(Alice, 209, false)).unwrap();
+		assert!(xt.is_really_verified());
+		assert_eq!(api.lookups(), 1);
+
+		// index 0 belongs to Bob at block 1, so the outcome must not be reused there.
+		pool.retain(|pooled| pooled.hash() != xt.hash());
+		assert!(pool.import_unchecked_extrinsic(BlockId::number(1), uxt(Alice, 209, false)).is_err());
+		assert_eq!(api.lookups(), 2);
+	}
+
+	#[test]
+	fn retain_should_drop_rejected_transactions() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let pool = TransactionPool::new(Default::default(), api.clone());
+		import_all(&pool, vec![uxt(Alice, 209, true), uxt(Alice, 210, true), uxt(Bob, 215, true)]);
+
+		let alice: AccountId = Alice.to_raw_public().into();
+		assert_eq!(pool.retain(|xt| xt.sender().ok() != Some(alice)), 2);
+
+		let ready = Ready::create(api.check_id(BlockId::number(0)).unwrap(), &*api);
+		let pending: Vec<_> = pool.cull_and_get_pending(ready, |p| p.map(|a| (a.sender().ok(), a.index())).collect());
+		assert_eq!(pending, vec![(Some(Bob.to_raw_public().into()), 215)]);
+	}
+
+	#[test]
+	fn retain_should_let_the_predicate_query_the_pool() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let pool = TransactionPool::new(Default::default(), api.clone());
+		import_all(&pool, vec![uxt(Alice, 209, true), uxt(Alice, 210, true), uxt(Bob, 215, true)]);
+
+		// keep only the transactions of senders with more than one.
+		let removed = pool.retain(|xt| {
+			let sender = xt.sender().unwrap();
+			pool.transactions_from(&sender).len() > 1
+		});
+		assert_eq!(removed, 1);
+		assert_eq!(pool.light_status().transaction_count, 2);
+	}
+
+	#[test]
+	fn inclusion_estimate_should_follow_rank() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let pool = TransactionPool::new(Default::default(), api.clone());
+		let xts = import_all(&pool, vec![
+			uxt(Alice, 209, true),
+			uxt(Alice, 210, true),
+			uxt(Alice, 211, true),
+			uxt(Alice, 213, true),
+		]);
+
+		let estimate = |i: usize| pool.estimated_blocks_until_inclusion(xts[i].hash(), BlockId::number(0), 2);
+		assert_eq!(estimate(0), Some(0));
+		assert_eq!(estimate(1), Some(0));
+		assert_eq!(estimate(2), Some(1));
+		// 212 is missing, so this one is future.
+		assert_eq!(estimate(3), None);
+	}
+
+	// start fetching Alice's index on another thread with `fetch`, once the fetch is
+	// shared by `sharers` evaluators besides the one fetching, and wait for it to be in flight.
+	fn lead_fetch<F>(fetches: &Arc<IndexFetches>, sharers: usize, fetch: F) -> thread::JoinHandle<Option<Index>> where
+		F: FnOnce() -> Option<Index> + Send + 'static
+	{
+		let (started, wait_started) = ::std::sync::mpsc::channel();
+		let leader = {
+			let fetches = fetches.clone();
+			thread::spawn(move || {
+				let inner = fetches.clone();
+				fetches.fetch(&BlockId::number(0), &Alice.to_raw_public().into(), move || {
+					started.send(()).unwrap();
+					// the registry and the leader hold the fetch as well.
+					while inner.in_flight.lock().first().map_or(0, |&(_, _, ref p)| Arc::strong_count(p)) < sharers + 2 {
+						thread::yield_now();
+					}
+					fetch()
+				})
+			})
+		};
+		wait_started.recv().unwrap();
+		leader
+	}
+
+	#[test]
+	fn concurrent_index_fetches_should_be_coalesced() {
+		let fetches = Arc::new(IndexFetches::default());
+		let leader = lead_fetch(&fetches, 1, || Some(209));
+
+		let index = fetches.fetch(&BlockId::number(0), &Alice.to_raw_public().into(), || Some(0));
+		assert_eq!(index, Some(209));
+		assert_eq!(leader.join().unwrap(), Some(209));
+		assert!(fetches.in_flight.lock().is_empty());
+	}
+
+	#[test]
+	fn waiters_should_be_woken_when_fetching_panics() {
+		let fetches = Arc::new(IndexFetches::default());
+		let leader = lead_fetch(&fetches, 1, || panic!("fetching failed"));
+
+		let index = fetches.fetch(&BlockId::number(0), &Alice.to_raw_public().into(), || Some(0));
+		assert_eq!(index, None);
+		assert!(leader.join().is_err());
+		assert!(fetches.in_flight.lock().is_empty());
+	}
+
+	#[test]
+	fn imported_transaction_should_keep_hash_integrity() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let pool = TransactionPool::new(Default::default(), api.clone());
+		let hash = *pool.import_unchecked_extrinsic(BlockId::number(0), uxt(Alice, 209, true)).unwrap().hash();
+
+		assert_eq!(pool.verify_hash_integrity(&hash), Some(true));
+		assert_eq!(pool.verify_hash_integrity(&Default::default()), None);
+		pool.assert_invariants();
+	}
+
+	#[test]
+	fn retry_verification_should_process_bounded_batches() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let options = Options { max_retries_per_call: 2, ..Default::default() };
+		let pool = TransactionPool::new(options, api.clone());
+		import_all(&pool, vec![uxt(Alice, 209, false), uxt(Bob, 215, false), uxt(Charlie, 163, false)]);
+
+		assert_eq!(pool.retry_verification(BlockId::number(0)).unwrap().more_remaining, true);
+		assert_eq!(api.lookups(), 2);
+		assert_eq!(pool.retry_verification(BlockId::number(0)).unwrap().more_remaining, false);
+		assert_eq!(api.lookups(), 3);
+
+		// all verified now, so there's nothing left to retry.
+		assert_eq!(pool.retry_verification(BlockId::number(0)).unwrap().more_remaining, false);
+		assert_eq!(api.lookups(), 3);
+	}
+
+	#[test]
+	fn transaction_missing_ready_deadline_should_be_dropped() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let pool = TransactionPool::new(Default::default(), api.clone());
+		pool.import_with_ready_deadline(BlockId::number(0), uxt(Alice, 215, true), 2).unwrap();
+
+		assert_eq!(pool.drop_missed_ready_deadlines(BlockId::number(1), 1).unwrap(), 0);
+		assert_eq!(pool.light_status().transaction_count, 1);
+
+		// the gap is never filled.
+		assert_eq!(pool.drop_missed_ready_deadlines(BlockId::number(2), 2).unwrap(), 1);
+		assert_eq!(pool.light_status().transaction_count, 0);
+	}
+
+	#[test]
+	fn removed_transactions_should_leave_no_ready_deadlines() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let pool = TransactionPool::new(Default::default(), api.clone());
+		let invalid = pool.import_with_ready_deadline(BlockId::number(0), uxt(Alice, 215, true), 2).unwrap();
+		pool.import_with_ready_deadline(BlockId::number(0), uxt(Bob, 217, true), 2).unwrap();
+
+		pool.remove_invalid(&[*invalid.hash()]);
+		assert_eq!(pool.ready_deadlines.lock().len(), 1);
+		pool.cull(None, |_: &VerifiedTransaction| Readiness::Stale);
+		assert!(pool.ready_deadlines.lock().is_empty());
+	}
+
+	#[test]
+	fn account_overview_should_describe_the_sender() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let options = Options { transaction_base_fee: 1, transaction_byte_fee: 2, ..Default::default() };
+		let pool = TransactionPool::new(options, api.clone());
+		let xts = import_all(&pool, vec![
+			uxt(Alice, 209, true),
+			uxt(Alice, 210, true),
+			uxt(Alice, 212, true),
+			uxt(Alice, 214, true),
+			uxt(Bob, 215, true),
+		]);
+
+		let overview = pool.account_overview(Alice.to_raw_public().into(), BlockId::number(0)).unwrap();
+		assert_eq!(overview, AccountOverview {
+			next_index: 209,
+			ready: vec![*xts[0].hash(), *xts[1].hash()],
+			future: vec![*xts[2].hash(), *xts[3].hash()],
+			gaps: vec![211, 213],
+			queued_fees: xts[..4].iter().map(|xt| 1 + 2 * xt.encoded_size() as u128).sum(),
+		});
+	}
+
+	#[test]
+	fn extrinsic_with_trailing_bytes_should_be_rejected() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let pool = TransactionPool::new(Default::default(), api.clone());
+
+		// drop the length prefix, which the primitive form lacks.
+		let mut xt: FutureProofUncheckedExtrinsic = uxt(Alice, 209, true).encode()[4..].to_vec();
+		xt.extend(&[1, 2, 3]);
+
+		match *super::ExtrinsicPool::submit(&pool, vec![xt]).unwrap_err().kind() {
+			ErrorKind::TrailingBytes(3) => {},
+			ref e => panic!("unexpected error: {:?}", e),
+		}
+		assert_eq!(pool.light_status().transaction_count, 0);
+	}
+
+	#[test]
+	fn transactions_should_be_bucketed_by_spec_version() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let pool = TransactionPool::new(Default::default(), api.clone());
+		let old = pool.import_for_spec(BlockId::number(0), uxt(Alice, 209, true), 1).unwrap();
+		let new = pool.import_for_spec(BlockId::number(0), uxt(Bob, 215, true), 2).unwrap();
+
+		let hashes = |spec| pool.transactions_for_spec(spec, BlockId::number(0)).unwrap()
+			.into_iter()
+			.map(|xt| *xt.hash())
+			.collect::<Vec<_>>();
+		assert_eq!(hashes(1), vec![*old.hash()]);
+		assert_eq!(hashes(2), vec![*new.hash()]);
+		assert_eq!(hashes(3), vec![]);
+	}
+
+	#[test]
+	fn retry_verification_should_promote_resolvable_transactions() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let pool = TransactionPool::new(Default::default(), api.clone());
+		let xt = import_all(&pool, vec![uxt(Alice, 209, false)]).pop().unwrap();
+		assert!(!xt.is_really_verified());
+
+		// at block 1 the index belongs to Bob, so the signature doesn't match.
+		let retried = pool.retry_verification(BlockId::number(1)).unwrap();
+		assert_eq!(retried.promoted, vec![]);
+		assert!(!xt.is_really_verified());
+
+		let retried = pool.retry_verification(BlockId::number(0)).unwrap();
+		assert_eq!(retried.promoted, vec![*xt.hash()]);
+		assert!(xt.is_really_verified());
+	}
+
+	#[test]
+	fn pre_insert_hook_should_veto_transactions() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let pool = TransactionPool::new(Default::default(), api.clone())
+			.with_pre_insert_hook(Box::new(|xt: &VerifiedTransaction, pool: &PoolView| {
+				xt.sender().map_or(true, |sender| pool.transactions_from(&sender).is_empty())
+			}));
+
+		pool.import_unchecked_extrinsic(BlockId::number(0), uxt(Alice, 209, true)).unwrap();
+		pool.import_unchecked_extrinsic(BlockId::number(0), uxt(Bob, 215, true)).unwrap();
+		match *pool.import_unchecked_extrinsic(BlockId::number(0), uxt(Alice, 210, true)).unwrap_err().kind() {
+			ErrorKind::VetoedByHook(_) => {},
+			ref e => panic!("unexpected error: {:?}", e),
+		}
+		assert_eq!(pool.light_status().transaction_count, 2);
+	}
+
+	#[test]
+	fn mem_usage_should_count_heap_allocations() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let pool = TransactionPool::new(Default::default(), api.clone());
+		let size = mem::size_of::<VerifiedTransaction>();
+
+		let plain = import_all(&pool, vec![uxt(Alice, 209, true)]).pop().unwrap();
+		assert!(plain.is_really_verified());
+		assert_eq!(plain.encoded.capacity(), plain.encoded_size());
+		assert_eq!(plain.mem_usage(), size + plain.encoded_size());
+
+		let votes = Call::Council(CouncilCall::set_approvals(vec![true; 100], 0));
+		let votes = import_all(&pool, vec![uxt_with_call(Bob, 215, true, votes)]).pop().unwrap();
+		let votes_heap = match votes.original.extrinsic.function {
+			Call::Council(CouncilCall::set_approvals(ref votes, _)) => votes.capacity(),
+			_ => unreachable!(),
+		};
+		assert!(votes_heap >= 100);
+		assert_eq!(votes.mem_usage(), size + votes.encoded_size() + 2 * votes_heap);
+
+		let code = PrivCall::Consensus(ConsensusPrivCall::set_code(vec![0; 64]));
+		let proposal = Call::Democracy(DemocracyCall::propose(Box::new(code), 0));
+		let proposal = import_all(&pool, vec![uxt_with_call(Charlie, 163, true, proposal)]).pop().unwrap();
+		let code_heap = match proposal.original.extrinsic.function {
+			Call::Democracy(DemocracyCall::propose(ref proposal, _)) => match **proposal {
+				PrivCall::Consensus(ConsensusPrivCall::set_code(ref code)) => code.capacity(),
+				_ => unreachable!(),
+			},
+			_ => unreachable!(),
+		};
+		assert!(code_heap >= 64);
+		assert_eq!(proposal.mem_usage(), size + proposal.encoded_size() + 2 * (mem::size_of::<PrivCall>() + code_heap));
+
+		assert_eq!(pool.light_status().mem_usage, plain.mem_usage() + votes.mem_usage() + proposal.mem_usage());
+	}
+
+	#[test]
+	fn fee_density_should_prefer_smaller_transactions() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let pool = TransactionPool::new(Default::default(), api.clone());
+		let xts = import_all(&pool, vec![uxt(Alice, 209, false), uxt(Bob, 215, true)]);
+		let (small, large) = (&xts[0], &xts[1]);
+		assert!(small.encoded_size() < large.encoded_size());
+
+		let scoring = Scoring::new(ScoringMode::FeeDensity, 100, 0);
+		assert_eq!(small.fee(100, 0), large.fee(100, 0));
+		assert!(scoring.score(small) > scoring.score(large));
+
+		let scoring = Scoring::new(ScoringMode::Flat, 100, 0);
+		assert_eq!(scoring.score(small), scoring.score(large));
+	}
+
+	#[test]
+	fn same_index_transaction_should_replace_when_allowed() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let options = Options { allow_replace: true, ..Default::default() };
+		let pool = TransactionPool::new(options, api.clone());
+		let bump = uxt_with_call(Alice, 209, true, Call::Timestamp(TimestampCall::set(1)));
+		import_all(&pool, vec![uxt(Alice, 209, true)]);
+		let new = import_all(&pool, vec![bump]).pop().unwrap();
+
+		let ready = Ready::create(api.check_id(BlockId::number(0)).unwrap(), &*api);
+		let pending: Vec<_> = pool.cull_and_get_pending(ready, |p| p.map(|a| *a.hash()).collect());
+		assert_eq!(pending, vec![*new.hash()]);
+	}
+
+	#[test]
+	fn same_index_transactions_should_both_be_kept_by_default() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let pool = TransactionPool::new(Default::default(), api.clone());
+		let bump = uxt_with_call(Alice, 209, true, Call::Timestamp(TimestampCall::set(1)));
+		let xts = import_all(&pool, vec![uxt(Alice, 209, true), bump]);
+
+		assert_eq!(pool.light_status().transaction_count, 2);
+		assert!(pool.contains(xts[0].hash()));
+		assert!(pool.contains(xts[1].hash()));
+	}
+
+	#[test]
+	fn frozen_pool_should_match_pending_transactions() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let pool = TransactionPool::new(Default::default(), api.clone());
+		// Alice's imported out of order, and Bob's following a gap.
+		import_all(&pool, vec![
+			uxt(Alice, 210, true),
+			uxt(Bob, 216, true),
+			uxt(Alice, 209, true),
+			uxt(Charlie, 163, true),
+			uxt(Alice, 212, true),
+		]);
+
+		let frozen = pool.freeze(BlockId::number(0)).unwrap();
+		let ready = Ready::create(api.check_id(BlockId::number(0)).unwrap(), &*api);
+		let pending: Vec<_> = pool.cull_and_get_pending(ready, |p| p.map(|xt| *xt.hash()).collect());
+		assert_eq!(frozen.ready().iter().map(|xt| *xt.hash()).collect::<Vec<_>>(), pending);
+
+		let mut future = frozen.future().iter().map(|xt| xt.index()).collect::<Vec<_>>();
+		future.sort();
+		assert_eq!(future, vec![212, 216]);
+	}
+
+	#[test]
+	fn frozen_pool_should_be_unaffected_by_changes() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let pool = TransactionPool::new(Default::default(), api.clone());
+		let xts = import_all(&pool, vec![uxt(Alice, 209, true), uxt(Alice, 211, true)]);
+
+		let frozen = pool.freeze(BlockId::number(0)).unwrap();
+		import_all(&pool, vec![uxt(Alice, 210, true), uxt(Bob, 215, true)]);
+		pool.prune_included(&[*xts[0].hash()]);
+
+		assert_eq!(frozen.len(), 2);
+		assert!(frozen.is_ready(xts[0].hash()));
+		assert!(!frozen.is_ready(xts[1].hash()));
+		assert_eq!(frozen.future().len(), 1);
+		assert_eq!(frozen.estimated_blocks_until_inclusion(xts[0].hash(), 1), Some(0));
+		assert_eq!(frozen.transactions_from(&Alice.to_raw_public().into()).len(), 2);
+		assert!(frozen.find(xts[1].hash()).is_some());
+	}
+
+	#[test]
+	fn transactions_far_ahead_of_reset_index_should_be_culled() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let pool = TransactionPool::new(Default::default(), api.clone());
+		import_all(&pool, vec![uxt(Alice, 209, true), uxt(Alice, 210, true), uxt(Bob, 215, true)]);
+
+		// Alice's index was reset, and Bob's account killed.
+		api.lower_index(Alice, 150);
+		api.lower_index(Bob, 0);
+		let ready = || Ready::create(api.check_id(BlockId::number(0)).unwrap(), &*api);
+
+		// without a threshold, they're kept around as future transactions.
+		assert_eq!(pool.cull_and_get_pending(ready(), |p| p.count()), 0);
+		assert_eq!(pool.light_status().transaction_count, 3);
+
+		// however far ahead they may be, Bob's follow nothing.
+		pool.cull_and_get_pending(ready().with_max_nonce_ahead(100), |p| p.count());
+		assert_eq!(pool.all_transactions().iter().map(|xt| xt.index()).collect::<Vec<_>>(), vec![209, 210]);
+
+		pool.cull_and_get_pending(ready().with_max_nonce_ahead(16), |p| p.count());
+		assert_eq!(pool.light_status().transaction_count, 0);
+	}
+
+	#[test]
+	fn address_lookups_should_be_cached_per_block() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let pool = TransactionPool::new(Default::default(), api.clone());
+
+		pool.import_unchecked_extrinsic(BlockId::number(0), uxt(Alice, 209, false)).unwrap();
+		pool.import_unchecked_extrinsic(BlockId::number(0), uxt(Alice, 210, false)).unwrap();
+		assert_eq!(api.lookups(), 1);
+
+		// the mapping from block 0 must not be used at block 1.
+		assert!(pool.import_unchecked_extrinsic(BlockId::number(1), uxt(Alice, 211, false)).is_err());
+		assert_eq!(api.lookups(), 2);
+	}
+
+	#[test]
+	fn future_transactions_of_reaped_account_should_be_culled() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let pool = TransactionPool::new(Default::default(), api.clone());
+		let xts = import_all(&pool, vec![uxt(Alice, 209, true), uxt(Alice, 211, true), uxt(Bob, 217, true)]);
+
+		// not flagged, so left to the usual culling.
+		assert_eq!(pool.note_account_reaped(Bob.to_raw_public().into(), BlockId::number(0)).unwrap(), 0);
+
+		pool.mark_reaping_pending(Alice.to_raw_public().into());
+		assert_eq!(pool.note_account_reaped(Alice.to_raw_public().into(), BlockId::number(0)).unwrap(), 1);
+		assert_eq!(pool.light_status().transaction_count, 2);
+		assert!(!pool.contains(xts[1].hash()));
+		assert!(pool.contains(xts[0].hash()));
+	}
+
+	#[test]
+	fn headroom_should_report_binding_constraint() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let limits = Limits { max_count: 100, max_per_sender: 3, max_mem_usage: 1 << 20 };
+		let pool = TransactionPool::new(Options { limits, ..Default::default() }, api.clone());
+		import_all(&pool, vec![uxt(Alice, 209, true), uxt(Alice, 210, true), uxt(Bob, 215, true)]);
+
+		let headroom = pool.headroom();
+		assert_eq!(headroom.count, 97);
+		assert_eq!(headroom.per_sender, 1);
+		assert_eq!(headroom.mem_usage, (1 << 20) - pool.light_status().mem_usage);
+		assert_eq!(headroom.binding(), Constraint::PerSender);
+	}
+
+	#[test]
+	fn mortal_transaction_should_be_culled_once_expired() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let pool = TransactionPool::new(Default::default(), api.clone());
+		// Alice's index at block 3 is 212.
+		let xt = pool.import_mortal(BlockId::number(0), uxt(Alice, 212, true), 2).unwrap();
+
+		let ready = Ready::create(api.check_id(BlockId::number(2)).unwrap(), &*api);
+		pool.cull(None, ready);
+		assert_eq!(pool.light_status().transaction_count, 1);
+
+		let ready = Ready::create(api.check_id(BlockId::number(3)).unwrap(), &*api);
+		let pending: Vec<_> = pool.cull_and_get_pending(ready, |p| p.map(|a| *a.hash()).collect());
+		assert_eq!(pending, vec![]);
+		assert!(!pool.contains(xt.hash()));
+	}
+
+	#[test]
+	fn remove_invalid_should_update_pending_set() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let pool = TransactionPool::new(Default::default(), api.clone());
+		let alice = pool.import_unchecked_extrinsic(BlockId::number(0), uxt(Alice, 209, false)).unwrap();
+		let bob = pool.import_unchecked_extrinsic(BlockId::number(0), uxt(Bob, 215, true)).unwrap();
+
+		let removed = pool.remove_invalid(&[*alice.hash()]);
+		assert_eq!(removed.len(), 1);
+		assert_eq!(removed[0].hash(), alice.hash());
+
+		let ready = Ready::create(api.check_id(BlockId::number(0)).unwrap(), &*api);
+		let pending: Vec<_> = pool.cull_and_get_pending(ready, |p| p.map(|a| *a.hash()).collect());
+		assert_eq!(pending, vec![*bob.hash()]);
+
+		// nothing about the removed transaction is remembered.
+		pool.import_unchecked_extrinsic(BlockId::number(0), uxt(Alice, 209, false)).unwrap();
+		assert_eq!(api.lookups(), 2);
+	}
+
+	#[test]
+	fn resolve_sender_should_follow_index_reassignment() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let pool = TransactionPool::new(Default::default(), api.clone());
+		let xt = pool.import_unchecked_extrinsic(BlockId::number(0), uxt(Alice, 209, false)).unwrap();
+
+		assert_eq!(xt.resolve_sender(BlockId::number(0), &*api).unwrap(), Some(Alice.to_raw_public().into()));
+		// index 0 belongs to Bob at block 1.
+		assert_eq!(xt.resolve_sender(BlockId::number(1), &*api).unwrap(), Some(Bob.to_raw_public().into()));
+		assert_eq!(xt.sender().ok(), Some(Alice.to_raw_public().into()));
+	}
+
+	#[test]
+	fn prewarm_should_use_a_single_batched_call() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let pool = TransactionPool::new(Default::default(), api.clone());
+		import_all(&pool, vec![uxt(Alice, 209, true), uxt(Bob, 215, true), uxt(Charlie, 163, true)]);
+
+		let mut ready = Ready::create(api.check_id(BlockId::number(0)).unwrap(), &*api);
+		ready.prewarm(&[Alice.to_raw_public().into(), Bob.to_raw_public().into(), Charlie.to_raw_public().into()]);
+		let pending: Vec<_> = pool.cull_and_get_pending(ready, |p| p.map(|a| a.index()).collect());
+
+		assert_eq!(pending.len(), 3);
+		assert_eq!(api.batch_indexes(), 1);
+		assert_eq!(api.indexes(), 0);
+	}
+
+	#[test]
+	fn memory_pressure_should_evict_lower_scored_transactions() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let probe = TransactionPool::new(Default::default(), api.clone());
+		let large = probe.import_unchecked_extrinsic(BlockId::number(0), uxt(Bob, 215, true)).unwrap().mem_usage();
+
+		let limits = Limits { max_count: 100, max_per_sender: 100, max_mem_usage: 2 * large };
+		let options = Options { limits, scoring_mode: ScoringMode::FeeDensity, transaction_base_fee: 100, ..Default::default() };
+		let pool = TransactionPool::new(options, api.clone());
+		let bob = pool.import_unchecked_extrinsic(BlockId::number(0), uxt(Bob, 215, true)).unwrap();
+		let charlie = pool.import_unchecked_extrinsic(BlockId::number(0), uxt(Charlie, 163, true)).unwrap();
+
+		// same fee in fewer bytes.
+		let alice = pool.import_unchecked_extrinsic(BlockId::number(0), uxt(Alice, 209, false)).unwrap();
+		assert_eq!(pool.light_status().transaction_count, 2);
+		assert!(pool.contains(alice.hash()));
+		assert!(!pool.contains(bob.hash()));
+		assert!(pool.contains(charlie.hash()));
+
+		// nothing scores lower than this one.
+		match *pool.import_unchecked_extrinsic(BlockId::number(0), uxt(Dave, 191, true)).unwrap_err().kind() {
+			ErrorKind::PoolFull => {},
+			ref e => panic!("unexpected error: {:?}", e),
+		}
+	}
+
+	#[test]
+	fn memory_pressure_should_evict_nothing_for_a_rejected_transaction() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let probe = TransactionPool::new(Default::default(), api.clone());
+		let size = probe.import_unchecked_extrinsic(BlockId::number(0), uxt(Bob, 215, true)).unwrap().mem_usage();
+
+		// full by count too, which the inner pool enforces by score rather than retention.
+		let limits = Limits { max_count: 1, max_per_sender: 100, max_mem_usage: size + size / 2 };
+		let options = Options {
+			limits,
+			future_half_life: Some(Duration::from_millis(50)),
+			..Default::default()
+		};
+		let pool = TransactionPool::new(options, api.clone());
+		// aged past many half lives, but scoring as high as any other.
+		let mut old = VerifiedTransaction::for_test(Bob, 217, Call::Timestamp(TimestampCall::set(0))).unwrap();
+		old.imported_at -= Duration::from_secs(1);
+		let old = pool.import_verified(BlockId::number(0), old).unwrap();
+
+		match *pool.import_unchecked_extrinsic(BlockId::number(0), uxt(Charlie, 163, true)).unwrap_err().kind() {
+			ErrorKind::Pool(_) => {},
+			ref e => panic!("unexpected error: {:?}", e),
+		}
+		assert!(pool.contains(old.hash()));
+		assert_eq!(pool.light_status().transaction_count, 1);
+	}
+
+	#[test]
+	fn fees_should_be_totalled_per_sender() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let options = Options { transaction_base_fee: 10, ..Default::default() };
+		let pool = TransactionPool::new(options, api.clone());
+		import_all(&pool, vec![
+			uxt(Alice, 209, true),
+			uxt(Alice, 210, true),
+			uxt(Bob, 215, true),
+			uxt(Charlie, 163, false),
+		]);
+
+		let fees = pool.fee_by_sender();
+		assert_eq!(fees.len(), 3);
+		assert_eq!(fees[&Some(Alice.to_raw_public().into())], 20);
+		assert_eq!(fees[&Some(Bob.to_raw_public().into())], 10);
+		// not resolved yet.
+		assert_eq!(fees[&None], 10);
+	}
+
+	#[test]
+	fn sender_limit_should_not_affect_other_senders() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let limits = Limits { max_per_sender: 2, ..Default::default() };
+		let pool = TransactionPool::new(Options { limits, ..Default::default() }, api.clone());
+		let import = |xt| pool.import_unchecked_extrinsic(BlockId::number(0), xt);
+
+		import(uxt(Alice, 209, true)).unwrap();
+		import(uxt(Alice, 210, false)).unwrap();
+		match *import(uxt(Alice, 211, true)).unwrap_err().kind() {
+			ErrorKind::TooManyFromSender(Some(who)) => assert_eq!(who, Alice.to_raw_public().into()),
+			ref e => panic!("unexpected error: {:?}", e),
+		}
+
+		import(uxt(Bob, 215, true)).unwrap();
+		import(uxt(Bob, 216, true)).unwrap();
+		assert_eq!(pool.light_status().transaction_count, 4);
+	}
+
+	#[test]
+	fn sender_limit_should_count_removed_transactions_no_more() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let limits = Limits { max_per_sender: 2, ..Default::default() };
+		let pool = TransactionPool::new(Options { limits, ..Default::default() }, api.clone());
+		let import = |xt| pool.import_unchecked_extrinsic(BlockId::number(0), xt);
+
+		let first = import(uxt(Alice, 209, true)).unwrap();
+		import(uxt(Alice, 210, true)).unwrap();
+		assert!(import(uxt(Alice, 211, true)).is_err());
+
+		pool.remove_invalid(&[*first.hash()]);
+		import(uxt(Alice, 211, true)).unwrap();
+		assert_eq!(pool.light_status().transaction_count, 2);
+	}
+
+	#[test]
+	fn sender_limit_should_evict_nothing_for_a_rejected_transaction() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let options = Options {
+			limits: Limits { max_per_sender: 2, ..Default::default() },
+			max_nonce_gap: Some(2),
+			scoring_mode: ScoringMode::FeeDensity,
+			transaction_base_fee: 100,
+			..Default::default()
+		};
+		let pool = TransactionPool::new(options, api.clone());
+		pool.import_unchecked_extrinsic(BlockId::number(0), uxt(Alice, 209, true)).unwrap();
+		// Alice's index at block 2 is 211.
+		let furthest = pool.import_unchecked_extrinsic(BlockId::number(2), uxt(Alice, 213, true)).unwrap();
+
+		// same fee in fewer bytes, but too far ahead.
+		match *pool.import_unchecked_extrinsic(BlockId::number(0), uxt(Alice, 212, false)).unwrap_err().kind() {
+			ErrorKind::NonceTooFarAhead(index, on_chain) => assert_eq!((index, on_chain), (212, 209)),
+			ref e => panic!("unexpected error: {:?}", e),
+		}
+		assert!(pool.contains(furthest.hash()));
+		assert_eq!(pool.light_status().transaction_count, 2);
+
+		let closer = pool.import_unchecked_extrinsic(BlockId::number(0), uxt(Alice, 211, false)).unwrap();
+		assert!(!pool.contains(furthest.hash()));
+		assert!(pool.contains(closer.hash()));
+		assert_eq!(pool.light_status().transaction_count, 2);
+	}
+
+	#[test]
+	fn older_future_transactions_should_be_evicted_first() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let probe = TransactionPool::new(Default::default(), api.clone());
+		let size = probe.import_unchecked_extrinsic(BlockId::number(0), uxt(Bob, 215, true)).unwrap().mem_usage();
+
+		let limits = Limits { max_count: 100, max_per_sender: 100, max_mem_usage: 2 * size };
+		let options = Options {
+			limits,
+			future_half_life: Some(Duration::from_millis(50)),
+			..Default::default()
+		};
+		let pool = TransactionPool::new(options, api.clone());
+		let recent = pool.import_unchecked_extrinsic(BlockId::number(0), uxt(Bob, 218, true)).unwrap();
+		// imported later, but aged past many half lives.
+		let mut old = VerifiedTransaction::for_test(Bob, 217, Call::Timestamp(TimestampCall::set(0))).unwrap();
+		old.imported_at -= Duration::from_secs(1);
+		let old = pool.import_verified(BlockId::number(0), old).unwrap();
+
+		let ready = pool.import_unchecked_extrinsic(BlockId::number(0), uxt(Bob, 215, true)).unwrap();
+		assert!(!pool.contains(old.hash()));
+		assert!(pool.contains(recent.hash()));
+		assert!(pool.contains(ready.hash()));
+	}
+
+	#[test]
+	fn transaction_status_should_follow_gap_filling() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let pool = TransactionPool::new(Default::default(), api.clone());
+		let ready = || Ready::create(api.check_id(BlockId::number(0)).unwrap(), &*api);
+
+		let future = import_all(&pool, vec![uxt(Alice, 210, true)]).pop().unwrap();
+		assert_eq!(pool.transaction_status(future.hash(), ready()), TransactionState::Future);
+		assert_eq!(pool.transaction_status(&Default::default(), ready()), TransactionState::Unknown);
+
+		let gap = import_all(&pool, vec![uxt(Alice, 209, true)]).pop().unwrap();
+		assert_eq!(pool.transaction_status(gap.hash(), ready()), TransactionState::Ready);
+		assert_eq!(pool.transaction_status(future.hash(), ready()), TransactionState::Ready);
+
+		// Alice's index at block 2 is 211.
+		let later = Ready::create(api.check_id(BlockId::number(2)).unwrap(), &*api);
+		assert_eq!(pool.transaction_status(gap.hash(), later), TransactionState::Stale);
+	}
+
+	#[test]
+	fn all_transactions_should_include_future_ones() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let pool = TransactionPool::new(Default::default(), api.clone());
+		import_all(&pool, vec![uxt(Alice, 211, true), uxt(Alice, 209, true)]);
+
+		let all: Vec<_> = pool.all_transactions().iter().map(|xt| xt.index()).collect();
+		assert_eq!(all, vec![209, 211]);
+
+		let ready = Ready::create(api.check_id(BlockId::number(0)).unwrap(), &*api);
+		let pending: Vec<_> = pool.cull_and_get_pending(ready, |p| p.map(|a| a.index()).collect());
+		assert_eq!(pending, vec![209]);
+		assert_eq!(pool.all_transactions().len(), 2);
+	}
+
+	#[test]
+	fn only_valid_transactions_should_be_exported() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let pool = TransactionPool::new(Default::default(), api.clone());
+		let xts = import_all(&pool, vec![uxt(Alice, 208, true), uxt(Alice, 209, true)]);
+
+		let exported = pool.export_valid_at(BlockId::number(0)).unwrap();
+		assert_eq!(exported, vec![xts[1].as_transaction().encode()]);
+		assert_eq!(super::decode_extrinsic(&exported[0]).unwrap(), *xts[1].as_transaction());
+	}
+
+	#[test]
+	fn repeatedly_failing_transaction_should_be_quarantined() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let options = Options { quarantine_after: Some(2), ..Default::default() };
+		let pool = TransactionPool::new(options, api.clone());
+		let xt = import_all(&pool, vec![uxt(Alice, 209, false)]).pop().unwrap();
+
+		// index 0 belongs to Bob at block 1, so verification fails there.
+		pool.retry_verification(BlockId::number(1)).unwrap();
+		assert!(pool.quarantined().is_empty());
+		pool.retry_verification(BlockId::number(1)).unwrap();
+		assert_eq!(pool.quarantined(), vec![*xt.hash()]);
+
+		// it would verify now, but isn't retried.
+		assert_eq!(pool.retry_verification(BlockId::number(0)).unwrap().promoted, vec![]);
+		assert!(!xt.is_really_verified());
+
+		pool.release_quarantined(&[*xt.hash()]);
+		assert_eq!(pool.retry_verification(BlockId::number(0)).unwrap().promoted, vec![*xt.hash()]);
+		assert!(pool.quarantined().is_empty());
+	}
+
+	#[test]
+	fn expired_transaction_should_leave_quarantine() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let options = Options { quarantine_after: Some(1), ttl: Some(Duration::from_secs(60)), ..Default::default() };
+		let pool = TransactionPool::new(options, api.clone());
+		let xt = import_all(&pool, vec![uxt(Alice, 209, false)]).pop().unwrap();
+
+		pool.retry_verification(BlockId::number(1)).unwrap();
+		assert_eq!(pool.quarantined(), vec![*xt.hash()]);
+		assert_eq!(pool.remove_expired_at(xt.imported_at() + Duration::from_secs(61)), vec![*xt.hash()]);
+		assert!(pool.quarantined().is_empty());
+	}
+
+	#[test]
+	fn subscribers_should_be_notified_of_imports_and_culls() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let pool = TransactionPool::new(Default::default(), api.clone());
+		let events = pool.subscribe();
+
+		let xt = pool.import_unchecked_extrinsic(BlockId::number(0), uxt(Alice, 209, true)).unwrap();
+		let stale = pool.import_unchecked_extrinsic(BlockId::number(0), uxt(Bob, 214, true)).unwrap();
+		pool.cull(None, Ready::create(api.check_id(BlockId::number(0)).unwrap(), &*api));
+		drop(pool);
+
+		let events = events.wait().collect::<::std::result::Result<Vec<_>, _>>().unwrap();
+		assert_eq!(events, vec![
+			PoolEvent::Imported(*xt.hash()),
+			PoolEvent::Imported(*stale.hash()),
+			PoolEvent::Culled(*stale.hash()),
+		]);
+	}
+
+	#[test]
+	fn explicit_scores_should_determine_ready_order() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let options = Options { scoring_mode: ScoringMode::Explicit, ..Default::default() };
+		let pool = TransactionPool::new(options, api.clone());
+
+		let xts = vec![uxt(Alice, 209, true), uxt(Bob, 215, true), uxt(Charlie, 163, true), uxt(Dave, 191, true)];
+		for (xt, score) in xts.iter().zip(&[20, 40, 10, 30]) {
+			pool.scoring().set_score(xt.using_encoded(BlakeTwo256::hash), *score);
+		}
+		for xt in xts {
+			pool.import_unchecked_extrinsic(BlockId::number(0), xt).unwrap();
+		}
+
+		let ready = Ready::create(pool.api.check_id(BlockId::number(0)).unwrap(), &*api);
+		let pending: Vec<_> = pool.cull_and_get_pending(ready, |p| p.map(|a| pool.scoring().score(a)).collect());
+		assert_eq!(pending, vec![40, 30, 20, 10]);
+	}
+
+	#[test]
+	fn misbehavior_reports_should_be_prioritised() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let pool = TransactionPool::new(Default::default(), api.clone());
+		let xts = import_all(&pool, vec![
+			uxt(Charlie, 163, false),
+			uxt(Alice, 209, true),
+			uxt_with_call(Bob, 215, true, misbehavior_report()),
+		]);
+		assert!(xts[2].is_misbehavior_report());
+		assert!(!xts[1].is_misbehavior_report());
+
+		let ready = Ready::create(api.check_id(BlockId::number(0)).unwrap(), &*api);
+		let pending: Vec<_> = pool.cull_and_get_pending(ready, |p| p.map(|a| *a.hash()).collect());
+		assert_eq!(pending, vec![*xts[2].hash(), *xts[1].hash(), *xts[0].hash()]);
+	}
+
+	#[test]
+	fn waiting_for_the_pool_lock_should_be_recorded() {
+		let api = Arc::new(TestPolkadotApi::default());
+		// every reading advances the clock by 10ms.
+		let (start, ticks) = (Instant::now(), AtomicUsize::new(0));
+		let clock = Box::new(move || start + Duration::from_millis(10 * ticks.fetch_add(1, AtomicOrdering::SeqCst) as u64));
+		let pool = Arc::new(TransactionPool::new(Default::default(), api.clone()).with_lock_clock(clock));
+		import_all(&pool, vec![uxt(Alice, 209, true)]);
+		assert_eq!(pool.lock_contention_stats().contended, 0);
+
+		let barrier = Arc::new(Barrier::new(2));
+		let waiter = {
+			let (pool, barrier) = (pool.clone(), barrier.clone());
+			thread::spawn(move || {
+				barrier.wait();
+				pool.light_status();
+			})
+		};
+
+		// readiness is evaluated with the pool locked.
+		pool.cull(None, |_: &VerifiedTransaction| {
+			barrier.wait();
+			thread::sleep(Duration::from_millis(50));
+			Readiness::Ready
+		});
+		waiter.join().unwrap();
+
+		let stats = pool.lock_contention_stats();
+		assert_eq!(stats.contended, 1);
+		assert_eq!(stats.total_wait, Duration::from_millis(10));
+		assert_eq!(stats.max_wait, Duration::from_millis(10));
+	}
+
+	#[test]
+	fn prefetched_senders_should_not_be_looked_up_again() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let pool = TransactionPool::new(Default::default(), api.clone());
+		import_all(&pool, vec![uxt(Alice, 209, true), uxt(Bob, 215, true)]);
+
+		let at = api.check_id(BlockId::number(0)).unwrap();
+		let ready = Ready::create_with_prefetch(at, &*api, &[Alice.to_raw_public().into()]);
+		assert_eq!(api.batch_indexes(), 1);
+		let status = pool.status(ready);
+
+		assert_eq!(status.ready, 2);
+		// only Bob wasn't prefetched.
+		assert_eq!(api.indexes(), 1);
+	}
+
+	#[test]
+	fn culling_same_index_transactions_should_ready_the_next_index() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let pool = TransactionPool::new(Default::default(), api.clone());
+		let bump = uxt_with_call(Alice, 209, false, Call::Timestamp(TimestampCall::set(1)));
+		// neither is verified on submission, so both are kept.
+		let xts = import_all(&pool, vec![uxt(Alice, 209, false), bump, uxt(Alice, 210, false)]);
+		assert_eq!(pool.light_status().transaction_count, 3);
+
+		let ready = Ready::create(api.check_id(BlockId::number(0)).unwrap(), &*api);
+		let pending: Vec<_> = pool.cull_and_get_pending(ready, |p| p.map(|a| a.index()).collect());
+		assert_eq!(pending, vec![209]);
+
+		// one of the 209s got included.
+		let ready = Ready::create(api.check_id(BlockId::number(1)).unwrap(), &*api);
+		let pending: Vec<_> = pool.cull_and_get_pending(ready, |p| p.map(|a| *a.hash()).collect());
+		assert_eq!(pending, vec![*xts[2].hash()]);
+		assert_eq!(pool.light_status().transaction_count, 1);
+	}
+
+	#[test]
+	fn replacement_should_report_the_replaced_transaction() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let options = Options { allow_replace: true, ..Default::default() };
+		let pool = TransactionPool::new(options, api.clone());
+		let old = pool.import_with_outcome(BlockId::number(0), uxt(Alice, 209, true)).unwrap();
+		assert!(old.replaced.is_none());
+
+		let bump = uxt_with_call(Alice, 209, true, Call::Timestamp(TimestampCall::set(1)));
+		let new = pool.import_with_outcome(BlockId::number(0), bump).unwrap();
+		assert_eq!(new.replaced.map(|xt| *xt.hash()), Some(*old.imported.hash()));
+		assert_eq!(pool.light_status().transaction_count, 1);
+
+		let next = pool.import_with_outcome(BlockId::number(0), uxt(Alice, 210, true)).unwrap();
+		assert!(next.replaced.is_none());
+	}
+
+	#[test]
+	fn test_transactions_should_be_evaluated_like_any_other() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let pool = TransactionPool::new(Default::default(), api.clone());
+		let call = || Call::Timestamp(TimestampCall::set(0));
+		let ready = pool.import_verified(BlockId::number(0), VerifiedTransaction::for_test(Alice, 209, call()).unwrap()).unwrap();
+		let future = pool.import_verified(BlockId::number(0), VerifiedTransaction::for_test(Alice, 211, call()).unwrap()).unwrap();
+		assert_eq!(ready.sender().ok(), Some(Alice.to_raw_public().into()));
+		assert_eq!(*ready.as_transaction(), uxt(Alice, 209, true));
+
+		let at = || Ready::create(api.check_id(BlockId::number(0)).unwrap(), &*api);
+		assert_eq!(pool.transaction_status(ready.hash(), at()), TransactionState::Ready);
+		assert_eq!(pool.transaction_status(future.hash(), at()), TransactionState::Future);
+	}
+
+	#[test]
+	fn primitive_extrinsics_should_decode_without_reencoding() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let pool = TransactionPool::new(Default::default(), api.clone());
+		let xt = pool.import_unchecked_extrinsic(BlockId::number(0), uxt(Alice, 209, true)).unwrap();
+		let primitive = xt.primitive_extrinsic().unwrap();
+		assert_eq!(super::decode_primitive_extrinsic(&primitive).unwrap(), *xt.as_transaction());
+
+		let pool = TransactionPool::new(Default::default(), api.clone());
+		pool.note_best_block([0; 32].into());
+		let hashes = super::ExtrinsicPool::submit(&pool, vec![primitive]).unwrap();
+		assert_eq!(hashes, vec![*xt.hash()]);
+	}
+
+	#[test]
+	fn competing_transactions_should_be_reported_as_conflicts() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let pool = TransactionPool::new(Default::default(), api.clone());
+		let import = |xt| pool.import_unchecked_extrinsic(BlockId::number(0), xt).unwrap();
+		// the same index from the same account under two addresses.
+		let by_id = import(uxt(Alice, 209, true));
+		let by_index = import(uxt(Alice, 209, false));
+		import(uxt(Alice, 210, true));
+		import(uxt(Bob, 215, true));
+
+		assert_eq!(pool.nonce_conflicts(), vec![
+			(Alice.to_raw_public().into(), 209, vec![*by_id.hash(), *by_index.hash()]),
+		]);
+	}
+
+	#[test]
+	fn transactions_expiring_now_should_not_be_offered_for_the_next_block() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let pool = TransactionPool::new(Default::default(), api.clone());
+		let expiring = pool.import_mortal(BlockId::number(0), uxt(Alice, 209, true), 0).unwrap();
+		let lasting = pool.import_mortal(BlockId::number(0), uxt(Bob, 215, true), 1).unwrap();
+
+		let ready = || Ready::create(api.check_id(BlockId::number(0)).unwrap(), &*api);
+		let mut pending: Vec<_> = pool.cull_and_get_pending(ready(), |p| p.map(|a| *a.hash()).collect());
+		pending.sort();
+		let mut expected = vec![*expiring.hash(), *lasting.hash()];
+		expected.sort();
+		assert_eq!(pending, expected);
+
+		let pending: Vec<_> = pool.cull_and_get_pending(ready().with_evaluate_as_of(EvaluateAsOf::NextBlock), |p| p.map(|a| *a.hash()).collect());
+		assert_eq!(pending, vec![*lasting.hash()]);
+	}
+
+	#[test]
+	fn proposed_batch_should_be_ordered_and_within_budget() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let options = Options { scoring_mode: ScoringMode::Explicit, ..Default::default() };
+		let pool = TransactionPool::new(options, api.clone());
+
+		let xts = vec![
+			uxt(Alice, 209, true),
+			uxt(Alice, 210, true),
+			uxt(Bob, 215, true),
+			uxt(Charlie, 165, true),
+			uxt(Dave, 191, true),
+		];
+		for (xt, score) in xts.iter().zip(&[20, 30, 40, 50, 25]) {
+			pool.scoring().set_score(xt.using_encoded(BlakeTwo256::hash), *score);
+		}
+		let size = xts[0].encode().len();
+		let xts = xts.into_iter()
+			.map(|xt| *pool.import_unchecked_extrinsic(BlockId::number(0), xt).unwrap().hash())
+			.collect::<Vec<_>>();
+
+		let batch = |max_encoded| pool.propose_batch(BlockId::number(0), max_encoded).unwrap()
+			.into_iter()
+			.map(|xt| *xt.hash())
+			.collect::<Vec<_>>();
+		// Charlie's is future; Alice's 210 only follows her 209.
+		assert_eq!(batch(10 * size), vec![xts[2], xts[4], xts[0], xts[1]]);
+		assert_eq!(batch(3 * size), vec![xts[2], xts[4], xts[0]]);
+		assert_eq!(batch(size - 1), vec![]);
+	}
+
+	#[test]
+	fn peers_should_not_be_broadcast_to_twice() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let pool = TransactionPool::new(Default::default(), api.clone());
+		let xt = import_all(&pool, vec![uxt(Alice, 209, true)]).pop().unwrap();
+		let candidates = vec!["A".to_owned(), "B".to_owned()];
+		assert_eq!(pool.peers_to_broadcast(xt.hash(), &candidates), candidates);
+
+		pool.on_broadcasted(vec![(*xt.hash(), vec!["A".to_owned()])].into_iter().collect());
+		assert_eq!(pool.peers_to_broadcast(xt.hash(), &candidates), vec!["B".to_owned()]);
+	}
+
+	#[test]
+	fn inherent_transactions_should_be_rejected_distinctly() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let pool = TransactionPool::new(Default::default(), api.clone());
+		let inherent = UncheckedExtrinsic::new(Extrinsic {
+			signed: Default::default(),
+			index: Default::default(),
+			function: Call::Timestamp(TimestampCall::set(100)),
+		}, Default::default());
+
+		let err = pool.import_unchecked_extrinsic(BlockId::number(0), inherent.clone()).unwrap_err();
+		assert!(err.is_inherent());
+		assert_eq!(err.inherent(), Some(&inherent));
+		assert_eq!(pool.light_status().transaction_count, 0);
+
+		pool.import_unchecked_extrinsic(BlockId::number(0), uxt(Alice, 209, true)).unwrap();
+		let err = pool.import_unchecked_extrinsic(BlockId::number(0), uxt(Alice, 209, true)).unwrap_err();
+		assert!(!err.is_inherent());
+	}
+
+	#[test]
+	fn unverified_import_should_not_touch_chain_state() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let pool = TransactionPool::new(Default::default(), api.clone());
+		let by_index = pool.import_unverified(uxt(Alice, 209, false)).unwrap();
+		let by_id = pool.import_unverified(uxt(Bob, 215, true)).unwrap();
+
+		assert!(!by_index.is_really_verified());
+		assert!(by_id.is_really_verified());
+		assert_eq!(pool.all_transactions().len(), 2);
+		assert_eq!((api.lookups(), api.indexes()), (0, 0));
+
+		let mut forged = uxt(Bob, 215, true);
+		forged.extrinsic.index = 216;
+		assert!(pool.import_unverified(forged).is_err());
+	}
+
+	#[test]
+	fn pending_for_sender_should_only_yield_ready_transactions_of_sender() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let pool = TransactionPool::new(Default::default(), api.clone());
+		import_all(&pool, vec![uxt(Alice, 210, true), uxt(Bob, 215, true), uxt(Alice, 209, true), uxt(Alice, 212, true)]);
+
+		let ready = Ready::create(api.check_id(BlockId::number(0)).unwrap(), &*api);
+		let pending = pool.pending_for_sender(Alice.to_raw_public().into(), ready);
+		assert_eq!(pending.iter().map(|xt| xt.index()).collect::<Vec<_>>(), vec![209, 210]);
+	}
+
+	#[test]
+	fn each_pass_should_start_from_the_chain_index() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let pool = TransactionPool::new(Default::default(), api.clone());
+		import_all(&pool, vec![uxt(Alice, 209, true), uxt(Alice, 211, true)]);
+
+		let mut ready = Ready::create(api.check_id(BlockId::number(0)).unwrap(), &*api);
+		let indices = |ready: &Ready<TestPolkadotApi>| pool.cull_and_get_pending(ready.clone(), |p| p.map(|a| a.index()).collect::<Vec<_>>());
+		assert_eq!(indices(&ready), vec![209]);
+
+		// the gap gets filled.
+		import_all(&pool, vec![uxt(Alice, 210, true)]);
+		assert_eq!(indices(&ready), vec![209, 210, 211]);
+
+		let xt = pool.all_transactions()[0].clone();
+		let is_ready = |ready: &mut Ready<TestPolkadotApi>| match ::extrinsic_pool::txpool::Ready::is_ready(ready, &*xt) {
+			Readiness::Ready => true,
+			_ => false,
+		};
+		assert!(is_ready(&mut ready));
+		assert!(!is_ready(&mut ready));
+		ready.reset();
+		assert!(is_ready(&mut ready));
+	}
+
+	#[test]
+	fn transactions_should_expire_after_ttl() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let options = Options { ttl: Some(Duration::from_secs(60)), ..Default::default() };
+		let pool = TransactionPool::new(options, api.clone());
+		let old = import_all(&pool, vec![uxt(Alice, 209, true)]).pop().unwrap();
+		assert!(pool.remove_expired().is_empty());
+		assert!(pool.remove_expired_at(old.imported_at() + Duration::from_secs(30)).is_empty());
+
+		import_all(&pool, vec![uxt(Alice, 210, true)]);
+		assert_eq!(pool.remove_expired_at(old.imported_at() + Duration::from_secs(61)), vec![*old.hash()]);
+		assert_eq!(pool.all_transactions().len(), 1);
+	}
+
+	#[test]
+	fn should_count_ready_and_future_transactions() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let pool = TransactionPool::new(Default::default(), api.clone());
+		import_all(&pool, vec![
+			uxt(Alice, 209, true),
+			uxt(Alice, 210, true),
+			uxt(Alice, 212, true),
+			uxt(Bob, 217, true),
+		]);
+
+		let ready = Ready::create(api.check_id(BlockId::number(0)).unwrap(), &*api);
+		assert_eq!(pool.ready_future_counts(ready), (2, 2));
+	}
+
+	#[test]
+	fn should_order_senders_deterministically_at_same_index() {
+		let scoring = Scoring::new(ScoringMode::Flat, 0, 0);
+		let alice = VerifiedTransaction::create(uxt(Alice, 5, true), usize::max_value()).unwrap();
+		let bob = VerifiedTransaction::create(uxt(Bob, 5, true), usize::max_value()).unwrap();
+		let compare = |a: &VerifiedTransaction, b: &VerifiedTransaction| ::extrinsic_pool::txpool::Scoring::compare(&scoring, a, b);
+
+		assert!(compare(&alice, &bob) != ::std::cmp::Ordering::Equal);
+		assert_eq!(compare(&alice, &bob), compare(&bob, &alice).reverse());
+		assert_eq!(compare(&alice, &alice.clone()), ::std::cmp::Ordering::Equal);
+
+		let mut one_way = vec![alice.clone(), bob.clone()];
+		let mut other_way = vec![bob, alice];
+		one_way.sort_by(|a, b| compare(a, b));
+		other_way.sort_by(|a, b| compare(a, b));
+		assert_eq!(
+			one_way.iter().map(|xt| *xt.hash()).collect::<Vec<_>>(),
+			other_way.iter().map(|xt| *xt.hash()).collect::<Vec<_>>(),
+		);
+	}
+
+	#[test]
+	fn should_order_transactions_of_an_account_alike_however_addressed() {
+		let scoring = Scoring::new(ScoringMode::Flat, 0, 0);
+		let by_id = VerifiedTransaction::create(uxt(Alice, 5, true), usize::max_value()).unwrap();
+		let by_index = VerifiedTransaction::create(uxt(Alice, 5, false), usize::max_value()).unwrap();
+		let bob = VerifiedTransaction::create(uxt(Bob, 5, true), usize::max_value()).unwrap();
+		let compare = |a: &VerifiedTransaction, b: &VerifiedTransaction| ::extrinsic_pool::txpool::Scoring::compare(&scoring, a, b);
+
+		// not resolved yet, so ahead of either.
+		assert_eq!(compare(&by_index, &by_id), ::std::cmp::Ordering::Less);
+		assert_eq!(compare(&by_index, &bob), ::std::cmp::Ordering::Less);
+
+		by_index.polish(|_| Ok(Alice.to_raw_public().into())).unwrap();
+		assert_eq!(compare(&by_index, &by_id), ::std::cmp::Ordering::Equal);
+		assert_eq!(compare(&by_index, &bob), compare(&by_id, &bob));
+	}
+
+	#[test]
+	fn primitive_conversion_should_fail_on_malformed_encoding() {
+		let encoded = uxt(Alice, 209, true).encode();
+		assert!(super::primitive_from_encoded(&encoded).is_ok());
+
+		// the length prefix claims more than there is.
+		match super::primitive_from_encoded(&encoded[..encoded.len() - 1]) {
+			Err(e) => match *e.kind() {
+				ErrorKind::InvalidExtrinsicFormat => {},
+				ref kind => panic!("unexpected error: {:?}", kind),
+			},
+			Ok(_) => panic!("conversion of malformed encoding should fail"),
+		}
+	}
+
+	#[test]
+	fn should_import_batch_reporting_each_outcome() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let pool = TransactionPool::new(Default::default(), api.clone());
+		let mut bad_signature = uxt(Bob, 215, true);
+		bad_signature.extrinsic.index = 216;
+
+		let outcomes = pool.import_many(BlockId::number(0), vec![
+			uxt(Alice, 209, true),
+			uxt(Alice, 209, true),
+			bad_signature,
+			uxt(Bob, 215, true),
+		]).unwrap();
+
+		assert_eq!(outcomes.len(), 4);
+		assert!(outcomes[0].is_ok());
+		assert!(outcomes[1].is_err());
+		assert!(outcomes[2].is_err());
+		assert!(outcomes[3].is_ok());
+		assert_eq!(pool.all_transactions().len(), 2);
+	}
+
+	#[test]
+	fn idempotent_import_should_yield_pooled_duplicate() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let pool = TransactionPool::new(Default::default(), api.clone());
+		let first = pool.import_unchecked_extrinsic_idempotent(BlockId::number(0), uxt(Alice, 209, true)).unwrap();
+
+		match pool.import_unchecked_extrinsic(BlockId::number(0), uxt(Alice, 209, true)) {
+			Err(e) => match *e.kind() {
+				ErrorKind::AlreadyImported(hash) => assert_eq!(hash, *first.hash()),
+				ref kind => panic!("unexpected error: {:?}", kind),
+			},
+			Ok(_) => panic!("duplicate should be rejected"),
+		}
+
+		let second = pool.import_unchecked_extrinsic_idempotent(BlockId::number(0), uxt(Alice, 209, true)).unwrap();
+		assert!(Arc::ptr_eq(&first, &second));
+		assert_eq!(pool.light_status().transaction_count, 1);
+	}
+
+	#[test]
+	fn metrics_should_count_submissions_and_duplicates() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let pool = TransactionPool::new(Default::default(), api.clone());
+		pool.import_unchecked_extrinsic(BlockId::number(0), uxt(Alice, 209, true)).unwrap();
+		assert!(pool.import_unchecked_extrinsic(BlockId::number(0), uxt(Alice, 209, true)).is_err());
+
+		let metrics = pool.metrics();
+		assert_eq!(metrics.submitted, 2);
+		assert_eq!(metrics.verified, 2);
+		assert_eq!(metrics.already_imported, 1);
+		assert_eq!(metrics.rejected, 0);
+	}
+
+	#[test]
+	fn should_list_transactions_needing_reverification() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let pool = TransactionPool::new(Default::default(), api.clone());
+		let known = pool.import_unchecked_extrinsic(BlockId::number(0), uxt(Alice, 209, true)).unwrap();
+		let unknown = import_all(&pool, vec![uxt(Bob, 215, false)]).pop().unwrap();
+
+		assert!(known.is_really_verified());
+		assert_eq!(pool.needs_reverification(), vec![*unknown.hash()]);
+	}
+
+	#[test]
+	fn should_prune_included_transactions() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let pool = TransactionPool::new(Default::default(), api.clone());
+		let included = pool.import_unchecked_extrinsic(BlockId::number(0), uxt(Alice, 209, true)).unwrap();
+		let remaining = pool.import_unchecked_extrinsic(BlockId::number(0), uxt(Alice, 210, true)).unwrap();
+
+		let pruned = pool.prune_included(&[*included.hash(), Hash::default()]);
+		assert_eq!(pruned.iter().map(|xt| *xt.hash()).collect::<Vec<_>>(), vec![*included.hash()]);
+
+		let ready = Ready::create(api.check_id(BlockId::number(1)).unwrap(), &*api);
+		let pending: Vec<_> = pool.cull_and_get_pending(ready, |p| p.map(|a| *a.hash()).collect());
+		assert_eq!(pending, vec![*remaining.hash()]);
+	}
+
+	#[test]
+	fn transactions_should_require_previous_index_of_sender() {
+		let xt = |who, index| VerifiedTransaction::create(uxt(who, index, true), usize::max_value()).unwrap();
+		let tag = |who: Keyring, index: Index| {
+			let mut tag = AccountId::from(who.to_raw_public()).encode();
+			tag.extend(index.encode());
+			tag
+		};
+
+		assert_eq!(xt(Alice, 5).provides(), vec![tag(Alice, 5)]);
+		assert_eq!(xt(Alice, 5).requires(), vec![tag(Alice, 4)]);
+		assert_eq!(xt(Alice, 5).requires(), xt(Alice, 4).provides());
+		assert!(xt(Bob, 5).requires() != xt(Alice, 4).provides());
+		assert!(xt(Alice, 0).requires().is_empty());
+	}
+
+	#[test]
+	fn clear_should_empty_pool() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let pool = TransactionPool::new(Default::default(), api.clone());
+		import_all(&pool, vec![uxt(Alice, 209, true), uxt(Alice, 210, true), uxt(Bob, 217, true)]);
+		assert_eq!(pool.light_status().transaction_count, 3);
+
+		pool.clear();
+		assert_eq!(pool.light_status().transaction_count, 0);
+		let ready = Ready::create(api.check_id(BlockId::number(0)).unwrap(), &*api);
+		let pending: Vec<_> = pool.cull_and_get_pending(ready, |p| p.map(|a| a.index()).collect());
+		assert_eq!(pending, vec![]);
+
+		// the pool remains usable.
+		pool.import_unchecked_extrinsic(BlockId::number(0), uxt(Alice, 209, true)).unwrap();
+		assert_eq!(pool.light_status().transaction_count, 1);
+	}
+
+	#[test]
+	fn should_reject_transactions_over_size_limit() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let limit = uxt(Alice, 209, true).encode().len();
+		let options = Options { max_transaction_size: limit, ..Default::default() };
+		let pool = TransactionPool::new(options, api.clone());
+		let large = uxt_with_call(Bob, 215, true, misbehavior_report());
+		let size = large.encode().len();
+		assert!(size > limit);
+
+		pool.import_unchecked_extrinsic(BlockId::number(0), uxt(Alice, 209, true)).unwrap();
+		match *pool.import_unchecked_extrinsic(BlockId::number(0), large.clone()).unwrap_err().kind() {
+			ErrorKind::TooLarge(s, l) => assert_eq!((s, l), (size, limit)),
+			ref kind => panic!("unexpected error: {:?}", kind),
+		}
+		assert!(pool.import_unverified(large).is_err());
+		assert_eq!(pool.light_status().transaction_count, 1);
+	}
+
+	#[test]
+	fn revalidation_should_reclassify_transactions() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let pool = TransactionPool::new(Default::default(), api.clone());
+		import_all(&pool, vec![
+			uxt(Alice, 209, true),
+			uxt(Alice, 210, true),
+			uxt(Alice, 212, true),
+			uxt(Bob, 215, false),
+		]);
+		assert_eq!(pool.last_validated_block(), None);
+
+		assert_eq!(pool.revalidate_at(BlockId::number(0)).unwrap(), RevalidationReport {
+			ready: 3,
+			future: 1,
+			stale: 0,
+			promoted: 1,
+		});
+
+		assert_eq!(pool.last_validated_block(), Some(BlockId::number(0)));
+
+		// indices advance by one at block 1.
+		assert_eq!(pool.revalidate_at(BlockId::number(1)).unwrap(), RevalidationReport {
+			ready: 1,
+			future: 1,
+			stale: 2,
+			promoted: 0,
+		});
+		assert_eq!(pool.last_validated_block(), Some(BlockId::number(1)));
+		assert_eq!(pool.light_status().transaction_count, 2);
+	}
+
+	#[test]
+	fn rejection_should_tell_reason() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let pool = TransactionPool::new(Default::default(), api.clone());
+		let mut tampered = uxt(Alice, 209, true);
+		tampered.extrinsic.index = 210;
+
+		let err = pool.import_unchecked_extrinsic(BlockId::number(0), tampered).unwrap_err();
+		match *err.kind() {
+			ErrorKind::BadSignature(_) => {},
+			ref kind => panic!("unexpected error: {:?}", kind),
+		}
+		assert!(format!("{}", err).contains("bad signature"));
+		assert_eq!(pool.metrics().rejected, 1);
+	}
+
+	#[test]
+	fn unknown_indices_should_be_cached_per_block() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let pool = TransactionPool::new(Default::default(), api.clone());
+		let unknown = |nonce| {
+			let mut uxt = uxt(Alice, nonce, false);
+			uxt.extrinsic.signed = RawAddress::Index(100);
+			uxt
+		};
+
+		let first = pool.import_unchecked_extrinsic(BlockId::number(0), unknown(209)).unwrap();
+		let second = pool.import_unchecked_extrinsic(BlockId::number(0), unknown(210)).unwrap();
+		assert!(!first.is_really_verified() && !second.is_really_verified());
+		assert_eq!(api.lookups(), 1);
+
+		// the index might be assigned by block 1.
+		pool.import_unchecked_extrinsic(BlockId::number(1), unknown(211)).unwrap();
+		assert_eq!(api.lookups(), 2);
+	}
+
+	#[test]
+	fn should_import_from_bytes() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let pool = TransactionPool::new(Default::default(), api.clone());
+		let encoded = uxt(Alice, 209, true).encode();
+
+		let is_format_error = |raw: &[u8]| match *pool.import_from_bytes(BlockId::number(0), raw).unwrap_err().kind() {
+			ErrorKind::InvalidExtrinsicFormat => true,
+			_ => false,
+		};
+		assert!(is_format_error(&encoded[..encoded.len() - 1]));
+		assert!(is_format_error(&[0xff; 8]));
+		assert_eq!(pool.light_status().transaction_count, 0);
+
+		let xt = pool.import_from_bytes(BlockId::number(0), &encoded).unwrap();
+		assert_eq!(*xt.as_transaction(), uxt(Alice, 209, true));
 	}
-}
 
-/// The polkadot transaction pool.
-///
-/// Wraps a `extrinsic_pool::Pool`.
-pub struct TransactionPool {
-	inner: Pool<UncheckedExtrinsic, Hash, Verifier, Scoring, Error>,
-}
+	#[test]
+	fn should_prioritise_with_custom_scoring() {
+		#[derive(Debug)]
+		struct HighestIndexFirst;
+		impl Prioritise for HighestIndexFirst {
+			fn priority(&self, xt: &VerifiedTransaction) -> u64 {
+				u64::from(xt.index())
+			}
+		}
 
-impl TransactionPool {
-	/// Create a new transaction pool.
-	pub fn new(options: Options) -> Self {
-		TransactionPool {
-			inner: Pool::new(options, Verifier, Scoring),
+		let api = Arc::new(TestPolkadotApi::default());
+		let scoring = Scoring::new(ScoringMode::Flat, 0, 0).with_priority(Arc::new(HighestIndexFirst));
+		let pool = TransactionPool::with_scoring(Default::default(), api.clone(), scoring);
+		import_all(&pool, vec![uxt(Alice, 209, true), uxt(Charlie, 163, true), uxt(Bob, 215, true)]);
+
+		let ready = Ready::create(api.check_id(BlockId::number(0)).unwrap(), &*api);
+		let pending: Vec<_> = pool.cull_and_get_pending(ready, |p| p.map(|a| a.index()).collect());
+		assert_eq!(pending, vec![215, 209, 163]);
+	}
+
+	#[test]
+	fn index_errors_should_be_counted_and_retried() {
+		let api = Arc::new(TestPolkadotApi { index_fails: true, ..Default::default() });
+		let pool = TransactionPool::new(Default::default(), api.clone());
+		let xt = import_all(&pool, vec![uxt(Alice, 209, true)]).pop().unwrap();
+
+		let mut ready = Ready::create(api.check_id(BlockId::number(0)).unwrap(), &*api);
+		let readiness = |ready: &mut Ready<TestPolkadotApi>| match ::extrinsic_pool::txpool::Ready::is_ready(ready, &*xt) {
+			Readiness::Future => "future",
+			Readiness::Ready => "ready",
+			Readiness::Stale => "stale",
+		};
+		assert_eq!(readiness(&mut ready), "future");
+		assert_eq!(readiness(&mut ready), "future");
+		assert_eq!((ready.index_errors(), api.indexes()), (2, 2));
+
+		// not culled, as the index may be fetched fine later.
+		assert_eq!(pool.cull(None, ready), 0);
+		assert_eq!(pool.light_status().transaction_count, 1);
+	}
+
+	#[test]
+	fn transactions_exceeding_memory_budget_should_be_rejected() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let probe = TransactionPool::new(Default::default(), api.clone());
+		let size = probe.import_unchecked_extrinsic(BlockId::number(0), uxt(Alice, 209, true)).unwrap().mem_usage();
+
+		let limits = Limits { max_count: 100, max_per_sender: 100, max_mem_usage: size - 1 };
+		let pool = TransactionPool::new(Options { limits, ..Default::default() }, api.clone());
+		match *pool.import_unchecked_extrinsic(BlockId::number(0), uxt(Alice, 209, true)).unwrap_err().kind() {
+			ErrorKind::PoolFull => {},
+			ref e => panic!("unexpected error: {:?}", e),
 		}
+		assert_eq!(pool.light_status().transaction_count, 0);
+		assert_eq!(pool.light_status().mem_usage, 0);
+
+		let limits = Limits { max_count: 100, max_per_sender: 100, max_mem_usage: size };
+		let pool = TransactionPool::new(Options { limits, ..Default::default() }, api.clone());
+		pool.import_unchecked_extrinsic(BlockId::number(0), uxt(Alice, 209, true)).unwrap();
+		assert_eq!(pool.light_status().mem_usage, size);
 	}
 
-	// TODO: remove. This is pointless - just use `submit()` directly.
-	pub fn import_unchecked_extrinsic(&self, uxt: UncheckedExtrinsic) -> Result<Arc<VerifiedTransaction>> {
-		self.inner.submit(vec![uxt]).map(|mut v| v.swap_remove(0))
+	#[test]
+	fn dry_run_should_not_import() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let pool = TransactionPool::new(Default::default(), api.clone());
+
+		let future = pool.dry_run(BlockId::number(0), uxt(Alice, 210, true)).unwrap();
+		assert_eq!(future.readiness, TransactionState::Future);
+		assert_eq!(future.sender, Some(Alice.to_raw_public().into()));
+		assert_eq!(pool.light_status().transaction_count, 0);
+		assert_eq!(pool.metrics(), PoolMetricsSnapshot::default());
+
+		pool.import_unchecked_extrinsic(BlockId::number(0), uxt(Alice, 209, true)).unwrap();
+		let next = pool.dry_run(BlockId::number(0), uxt(Alice, 210, true)).unwrap();
+		assert_eq!(next.readiness, TransactionState::Ready);
+		assert!(!pool.contains(&next.hash));
+		assert_eq!(pool.dry_run(BlockId::number(0), uxt(Alice, 208, true)).unwrap().readiness, TransactionState::Stale);
+
+		// following a pooled transaction whose sender isn't resolved yet.
+		let unverified = pool.import_unverified(uxt(Bob, 215, false)).unwrap();
+		let (verification_cache, address_cache) = (pool.verification_cache.lock().outcomes.len(), pool.address_cache.lock().ids.len());
+		let next = pool.dry_run(BlockId::number(0), uxt(Bob, 216, false)).unwrap();
+		assert_eq!(next.readiness, TransactionState::Ready);
+		assert!(!unverified.is_really_verified());
+		assert_eq!(pool.needs_reverification(), vec![*unverified.hash()]);
+		assert_eq!(pool.verification_cache.lock().outcomes.len(), verification_cache);
+		assert_eq!(pool.address_cache.lock().ids.len(), address_cache);
 	}
-}
 
-impl Deref for TransactionPool {
-	type Target = Pool<UncheckedExtrinsic, Hash, Verifier, Scoring, Error>;
+	#[test]
+	fn failing_retries_should_back_off_until_dropped() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let options = Options {
+			retry_backoff: Some(Duration::from_secs(10)),
+			drop_unverified_after: Some(3),
+			..Default::default()
+		};
+		let pool = TransactionPool::new(options, api.clone());
+		let xt = import_all(&pool, vec![uxt(Alice, 209, false)]).pop().unwrap();
+		let start = Instant::now();
+		// the index belongs to others at these blocks, so verification keeps failing.
+		let retry = |block, secs| pool.retry_verification_at(BlockId::number(block), start + Duration::from_secs(secs)).unwrap();
 
-	fn deref(&self) -> &Self::Target {
-		&self.inner
+		retry(1, 0);
+		assert_eq!(api.lookups(), 1);
+		retry(2, 9);
+		assert_eq!(api.lookups(), 1);
+		retry(2, 10);
+		assert_eq!(api.lookups(), 2);
+
+		// twice as long after the second failure.
+		retry(3, 29);
+		assert_eq!(api.lookups(), 2);
+		let retried = retry(3, 30);
+		assert_eq!(api.lookups(), 3);
+		assert_eq!(retried.dropped, vec![*xt.hash()]);
+		assert!(!pool.contains(xt.hash()));
 	}
-}
 
-impl ExtrinsicPool<FutureProofUncheckedExtrinsic, Hash> for TransactionPool {
-	type Error = Error;
+	#[test]
+	fn known_nonces_should_take_precedence_over_chain_state() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let pool = TransactionPool::new(Default::default(), api.clone());
+		import_all(&pool, vec![uxt(Alice, 209, true), uxt(Alice, 210, true)]);
 
-	fn submit(&self, xts: Vec<FutureProofUncheckedExtrinsic>) -> Result<Vec<Hash>> {
-		// TODO: more general transaction pool, which can handle more kinds of vec-encoded transactions,
-		// even when runtime is out of date.
-		xts.into_iter()
-			.map(|xt| xt.encode())
-			.map(|encoded| UncheckedExtrinsic::decode(&mut &encoded[..]))
-			.map(|maybe_decoded| maybe_decoded.ok_or_else(|| ErrorKind::InvalidExtrinsicFormat.into()))
-			.map(|x| x.and_then(|x| self.import_unchecked_extrinsic(x)))
-			.map(|x| x.map(|x| x.hash().clone()))
-			.collect()
+		let at = api.check_id(BlockId::number(0)).unwrap();
+		let nonces = vec![(Alice.to_raw_public().into(), 210)].into_iter().collect();
+		let ready = Ready::with_known_nonces(at, &*api, nonces);
+		let pending: Vec<_> = pool.cull_and_get_pending(ready, |p| p.map(|a| a.index()).collect());
+
+		assert_eq!(pending, vec![210]);
+		assert_eq!(api.indexes(), 0);
 	}
-}
 
-#[cfg(test)]
-mod tests {
-	use super::{TransactionPool, Ready};
-	use substrate_keyring::Keyring::{self, *};
-	use codec::Slicable;
-	use polkadot_api::{PolkadotApi, BlockBuilder, CheckedBlockId, Result};
-	use primitives::{AccountId, AccountIndex, Block, BlockId, Hash, Index, SessionKey, Timestamp,
-		UncheckedExtrinsic as FutureProofUncheckedExtrinsic};
-	use runtime::{RawAddress, Call, TimestampCall, BareExtrinsic, Extrinsic, UncheckedExtrinsic};
-	use primitives::parachain::{CandidateReceipt, DutyRoster, Id as ParaId};
-	use substrate_runtime_primitives::{MaybeUnsigned, generic};
+	#[test]
+	fn transactions_should_be_counted_per_sender() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let pool = TransactionPool::new(Default::default(), api.clone());
+		import_all(&pool, vec![
+			uxt(Alice, 209, true),
+			uxt(Alice, 210, true),
+			uxt(Alice, 212, true),
+			uxt(Bob, 215, true),
+			uxt(Charlie, 163, false),
+		]);
 
-	struct TestBlockBuilder;
-	impl BlockBuilder for TestBlockBuilder {
-		fn push_extrinsic(&mut self, _extrinsic: FutureProofUncheckedExtrinsic) -> Result<()> { unimplemented!() }
-		fn bake(self) -> Result<Block> { unimplemented!() }
+		let histogram = pool.sender_histogram();
+		assert_eq!(histogram.len(), 3);
+		assert_eq!(histogram[&Some(Alice.to_raw_public().into())], 3);
+		assert_eq!(histogram[&Some(Bob.to_raw_public().into())], 1);
+		assert_eq!(histogram[&None], 1);
+		assert_eq!(pool.light_status().transaction_count, 5);
 	}
 
-	#[derive(Clone)]
-	struct TestCheckedBlockId(pub BlockId);
-	impl CheckedBlockId for TestCheckedBlockId {
-		fn block_id(&self) -> &BlockId { &self.0 }
+	#[test]
+	fn pending_should_be_judged_at_the_given_block() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let pool = TransactionPool::new(Default::default(), api.clone());
+		import_all(&pool, vec![uxt(Alice, 209, true), uxt(Alice, 210, true)]);
+		let indices = |number| pool.pending_at(BlockId::number(number)).unwrap()
+			.into_iter()
+			.map(|xt| xt.index())
+			.collect::<Vec<_>>();
+
+		assert_eq!(indices(0), vec![209, 210]);
+		assert_eq!(indices(1), vec![210]);
+		// stale at block 1, but still there for block 0.
+		assert_eq!(pool.light_status().transaction_count, 2);
+		assert_eq!(indices(0), vec![209, 210]);
 	}
 
-	fn number_of(at: &TestCheckedBlockId) -> u32 {
-		match at.0 {
-			generic::BlockId::Number(n) => n as u32,
-			_ => 0,
-		}
+	#[test]
+	fn full_pool_should_admit_higher_ranked_transactions_only() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let limits = Limits { max_count: 2, ..Default::default() };
+		let pool = TransactionPool::new(Options { limits, ..Default::default() }, api.clone());
+		pool.import_unchecked_extrinsic(BlockId::number(0), uxt(Alice, 209, true)).unwrap();
+		pool.import_unchecked_extrinsic(BlockId::number(0), uxt(Bob, 215, true)).unwrap();
+
+		// ordinary transactions all rank the same, so nothing is displaced.
+		assert!(pool.import_unchecked_extrinsic(BlockId::number(0), uxt(Dave, 191, true)).is_err());
+		assert_eq!(pool.light_status().transaction_count, 2);
+
+		let report = pool.import_unchecked_extrinsic(BlockId::number(0), uxt_with_call(Charlie, 163, true, misbehavior_report())).unwrap();
+		assert_eq!(pool.light_status().transaction_count, 2);
+		assert!(pool.contains(report.hash()));
 	}
 
-	#[derive(Clone)]
-	struct TestPolkadotApi;
-	impl PolkadotApi for TestPolkadotApi {
-		type CheckedBlockId = TestCheckedBlockId;
-		type BlockBuilder = TestBlockBuilder;
+	#[test]
+	fn later_transaction_should_not_displace_earlier_of_same_sender() {
+		use extrinsic_pool::txpool::Scoring as ScoringOps;
 
-		fn check_id(&self, id: BlockId) -> Result<TestCheckedBlockId> { Ok(TestCheckedBlockId(id)) }
-		fn session_keys(&self, _at: &TestCheckedBlockId) -> Result<Vec<SessionKey>> { unimplemented!() }
-		fn validators(&self, _at: &TestCheckedBlockId) -> Result<Vec<AccountId>> { unimplemented!() }
-		fn random_seed(&self, _at: &TestCheckedBlockId) -> Result<Hash> { unimplemented!() }
-		fn duty_roster(&self, _at: &TestCheckedBlockId) -> Result<DutyRoster> { unimplemented!() }
-		fn timestamp(&self, _at: &TestCheckedBlockId) -> Result<u64> { unimplemented!() }
-		fn evaluate_block(&self, _at: &TestCheckedBlockId, _block: Block) -> Result<bool> { unimplemented!() }
-		fn active_parachains(&self, _at: &TestCheckedBlockId) -> Result<Vec<ParaId>> { unimplemented!() }
-		fn parachain_code(&self, _at: &TestCheckedBlockId, _parachain: ParaId) -> Result<Option<Vec<u8>>> { unimplemented!() }
-		fn parachain_head(&self, _at: &TestCheckedBlockId, _parachain: ParaId) -> Result<Option<Vec<u8>>> { unimplemented!() }
-		fn build_block(&self, _at: &TestCheckedBlockId, _timestamp: Timestamp, _new_heads: Vec<CandidateReceipt>) -> Result<Self::BlockBuilder> { unimplemented!() }
-		fn inherent_extrinsics(&self, _at: &TestCheckedBlockId, _timestamp: Timestamp, _new_heads: Vec<CandidateReceipt>) -> Result<Vec<Vec<u8>>> { unimplemented!() }
+		let scoring = Scoring::new(ScoringMode::Explicit, 0, 0);
+		let earlier = VerifiedTransaction::create(uxt(Alice, 209, true), usize::max_value()).unwrap();
+		let later = VerifiedTransaction::create(uxt(Alice, 210, true), usize::max_value()).unwrap();
+		scoring.set_score(*earlier.hash(), 10);
+		scoring.set_score(*later.hash(), 20);
 
-		fn index(&self, _at: &TestCheckedBlockId, _account: AccountId) -> Result<Index> {
-			Ok((_account[0] as u32) + number_of(_at))
+		assert!(!scoring.should_replace(&earlier, &later));
+		assert!(!scoring.should_replace(&later, &earlier));
+		scoring.set_score(*earlier.hash(), 30);
+		assert!(scoring.should_replace(&later, &earlier));
+
+		// the same account, given by index once resolved.
+		let indexed = VerifiedTransaction::create(uxt(Alice, 210, false), usize::max_value()).unwrap();
+		indexed.polish(|_| Ok(Alice.to_raw_public().into())).unwrap();
+		scoring.set_score(*indexed.hash(), 40);
+		assert!(!scoring.should_replace(&earlier, &indexed));
+	}
+
+	#[test]
+	fn watcher_should_follow_transaction_state() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let pool = TransactionPool::new(Default::default(), api.clone());
+		let future = uxt(Alice, 210, true);
+		let hash = BlakeTwo256::hash(&future.encode());
+		let watcher = pool.watch(hash);
+		let unrelated = pool.watch(Default::default());
+
+		pool.import_unchecked_extrinsic(BlockId::number(0), future).unwrap();
+		pool.import_unchecked_extrinsic(BlockId::number(0), uxt(Alice, 209, true)).unwrap();
+		pool.on_broadcasted(vec![(hash, vec!["peer".to_owned()])].into_iter().collect());
+		pool.remove_invalid(&[hash]);
+
+		let events = watcher.wait().collect::<::std::result::Result<Vec<_>, _>>().unwrap();
+		assert_eq!(events, vec![
+			WatchEvent::Future,
+			WatchEvent::Ready,
+			WatchEvent::Broadcast(vec!["peer".to_owned()]),
+			WatchEvent::Invalid,
+		]);
+
+		assert_eq!(pool.watchers.lock().len(), 1);
+		drop(unrelated);
+		assert!(pool.watchers.lock().is_empty());
+	}
+
+	#[test]
+	fn checked_block_should_be_reused_while_unchanged() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let pool = TransactionPool::new(Default::default(), api.clone());
+
+		// blocks given by hash, numbered 0 and 1 by `TestPolkadotApi`.
+		let (first, second) = (BlockId::hash([0; 32].into()), BlockId::hash([1; 32].into()));
+		pool.import_unchecked_extrinsic(first, uxt(Alice, 209, true)).unwrap();
+		pool.import_unchecked_extrinsic(first, uxt(Bob, 215, true)).unwrap();
+		assert_eq!(api.check_ids(), 1);
+
+		pool.import_unchecked_extrinsic(second, uxt(Alice, 210, true)).unwrap();
+		pool.import_unchecked_extrinsic(first, uxt(Charlie, 163, true)).unwrap();
+		assert_eq!(api.check_ids(), 3);
+
+		// a number may refer to another block after a reorganisation.
+		pool.import_unchecked_extrinsic(BlockId::number(0), uxt(Dave, 191, true)).unwrap();
+		pool.import_unchecked_extrinsic(BlockId::number(0), uxt(Dave, 192, true)).unwrap();
+		assert_eq!(api.check_ids(), 5);
+	}
+
+	#[test]
+	fn caches_should_follow_the_best_block() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let pool = TransactionPool::new(Default::default(), api.clone());
+		// two blocks competing for the same height, where Alice's index and address differ.
+		let (first, second) = (BlockId::hash([0; 32].into()), BlockId::hash([1; 32].into()));
+
+		pool.import_unchecked_extrinsic(first, uxt(Alice, 209, false)).unwrap();
+		assert!(pool.import_unchecked_extrinsic(second, uxt(Alice, 210, false)).is_err());
+		let indices = |at| pool.pending_at(at).unwrap().into_iter().map(|xt| xt.index()).collect::<Vec<_>>();
+		assert_eq!(indices(first), vec![209]);
+		assert_eq!(indices(second), vec![]);
+
+		// what was remembered at a block given by number is forgotten on a reorganisation.
+		pool.import_unchecked_extrinsic(BlockId::number(0), uxt(Alice, 211, false)).unwrap();
+		assert_eq!(api.lookups(), 3);
+		pool.note_best_block([0; 32].into());
+		pool.import_unchecked_extrinsic(BlockId::number(0), uxt(Alice, 212, false)).unwrap();
+		assert_eq!(api.lookups(), 4);
+
+		// while what was remembered at a block given by hash is kept.
+		pool.import_unchecked_extrinsic(first, uxt(Alice, 213, false)).unwrap();
+		assert_eq!(api.lookups(), 5);
+		pool.note_best_block([1; 32].into());
+		pool.import_unchecked_extrinsic(first, uxt(Alice, 214, false)).unwrap();
+		assert_eq!(api.lookups(), 5);
+	}
+
+	#[test]
+	fn submit_each_should_report_every_outcome() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let pool = TransactionPool::new(Default::default(), api.clone());
+		let inherent = UncheckedExtrinsic::new(Extrinsic {
+			signed: Default::default(),
+			index: Default::default(),
+			function: Call::Timestamp(TimestampCall::set(100)),
+		}, Default::default());
+		let valid = uxt(Alice, 209, true);
+		// drop the length prefixes, which the primitive form lacks.
+		let primitive = |xt: &UncheckedExtrinsic| xt.encode()[4..].to_vec();
+
+		pool.note_best_block([0; 32].into());
+		let results = pool.submit_each(vec![primitive(&inherent), primitive(&valid)]);
+		assert_eq!(results.len(), 2);
+		assert!(results[0].as_ref().unwrap_err().is_inherent());
+		assert_eq!(*results[1].as_ref().unwrap(), BlakeTwo256::hash(&valid.encode()));
+		assert_eq!(pool.light_status().transaction_count, 1);
+	}
+
+	#[test]
+	fn submitted_transactions_should_be_imported_at_the_best_block() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let pool = TransactionPool::new(Default::default(), api.clone());
+		let events = pool.subscribe();
+		// drop the length prefixes, which the primitive form lacks.
+		let primitive = |xt: &UncheckedExtrinsic| xt.encode()[4..].to_vec();
+
+		match *pool.submit_each(vec![primitive(&uxt(Alice, 210, true))])[0].as_ref().unwrap_err().kind() {
+			ErrorKind::NoBestBlock => {},
+			ref e => panic!("unexpected error: {:?}", e),
 		}
-		fn lookup(&self, _at: &TestCheckedBlockId, _address: RawAddress<AccountId, AccountIndex>) -> Result<Option<AccountId>> {
-			match _address {
-				RawAddress::Id(i) => Ok(Some(i)),
-				RawAddress::Index(i) => Ok(match (i < 8, i + (number_of(_at) as u64) % 8) {
-					(false, _) => None,
-					(_, 0) => Some(Alice.to_raw_public().into()),
-					(_, 1) => Some(Bob.to_raw_public().into()),
-					(_, 2) => Some(Charlie.to_raw_public().into()),
-					(_, 3) => Some(Dave.to_raw_public().into()),
-					(_, 4) => Some(Eve.to_raw_public().into()),
-					(_, 5) => Some(Ferdie.to_raw_public().into()),
-					(_, 6) => Some(One.to_raw_public().into()),
-					(_, 7) => Some(Two.to_raw_public().into()),
-					_ => None,
-				}),
+
+		pool.note_best_block([0; 32].into());
+		let future = super::ExtrinsicPool::submit(&pool, vec![primitive(&uxt(Alice, 210, true))]).unwrap();
+		let gap = super::ExtrinsicPool::submit(&pool, vec![primitive(&uxt(Alice, 209, true))]).unwrap();
+		let ready = Ready::create(api.check_id(BlockId::number(0)).unwrap(), &*api);
+		assert_eq!(pool.cull_and_get_pending(ready, |pending| pending.count()), 2);
+		assert_eq!(pool.metrics().submitted, 2);
+		drop(pool);
+
+		let events = events.wait().collect::<::std::result::Result<Vec<_>, _>>().unwrap();
+		assert_eq!(events, vec![
+			PoolEvent::Imported(future[0]),
+			PoolEvent::Imported(gap[0]),
+			PoolEvent::BecameReady(future[0]),
+		]);
+	}
+
+	#[test]
+	fn banned_senders_should_be_rejected() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let pool = TransactionPool::new(Default::default(), api.clone());
+		let alice: AccountId = Alice.to_raw_public().into();
+		pool.ban_sender(alice);
+		assert!(pool.is_banned(&alice));
+
+		for xt in vec![uxt(Alice, 209, true), uxt(Alice, 209, false)] {
+			match *pool.import_unchecked_extrinsic(BlockId::number(0), xt).unwrap_err().kind() {
+				ErrorKind::Banned(who) => assert_eq!(who, alice),
+				ref e => panic!("unexpected error: {:?}", e),
 			}
 		}
+		assert!(pool.import_unverified(uxt(Alice, 209, true)).is_err());
+		pool.import_unchecked_extrinsic(BlockId::number(0), uxt(Bob, 215, true)).unwrap();
+		assert_eq!(pool.light_status().transaction_count, 1);
+
+		// only found to be Alice's once her index is resolved.
+		let unresolved = import_all(&pool, vec![uxt(Alice, 210, false)]).pop().unwrap();
+		let retried = pool.retry_verification(BlockId::number(0)).unwrap();
+		assert_eq!(retried.promoted, vec![]);
+		assert_eq!(retried.dropped, vec![*unresolved.hash()]);
+		assert!(!pool.contains(unresolved.hash()));
+
+		pool.unban_sender(&alice);
+		pool.import_unchecked_extrinsic(BlockId::number(0), uxt(Alice, 209, true)).unwrap();
 	}
 
-	fn uxt(who: Keyring, nonce: Index, use_id: bool) -> UncheckedExtrinsic {
-		let sxt = BareExtrinsic {
-			signed: who.to_raw_public().into(),
-			index: nonce,
-			function: Call::Timestamp(TimestampCall::set(0)),
-		};
-		let sig = sxt.using_encoded(|e| who.sign(e));
-		UncheckedExtrinsic::new(Extrinsic {
-			signed: if use_id { RawAddress::Id(sxt.signed) } else { RawAddress::Index(
-				match who {
-					Alice => 0,
-					Bob => 1,
-					Charlie => 2,
-					Dave => 3,
-					Eve => 4,
-					Ferdie => 5,
-					One => 6,
-					Two => 7,
-				}
-			)},
-			index: sxt.index,
-			function: sxt.function,
-		}, MaybeUnsigned(sig.into())).using_encoded(|e| UncheckedExtrinsic::decode(&mut &e[..])).unwrap()
+	#[test]
+	fn subscribers_should_be_notified_of_future_transactions_becoming_ready() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let pool = TransactionPool::new(Default::default(), api.clone());
+		let events = pool.subscribe();
+
+		let future = pool.import_unchecked_extrinsic(BlockId::number(0), uxt(Alice, 210, true)).unwrap();
+		let gap = pool.import_unchecked_extrinsic(BlockId::number(0), uxt(Alice, 209, true)).unwrap();
+		let ready = Ready::create(api.check_id(BlockId::number(0)).unwrap(), &*api);
+		assert_eq!(pool.cull_and_get_pending(ready.clone(), |pending| pending.count()), 2);
+		// reported only once.
+		pool.cull_and_get_pending(ready, |pending| pending.count());
+		drop(pool);
+
+		let events = events.wait().collect::<::std::result::Result<Vec<_>, _>>().unwrap();
+		assert_eq!(events, vec![
+			PoolEvent::Imported(*future.hash()),
+			PoolEvent::Imported(*gap.hash()),
+			PoolEvent::BecameReady(*future.hash()),
+		]);
 	}
 
 	#[test]
-	fn id_submission_should_work() {
-		let pool = TransactionPool::new(Default::default());
-		pool.submit(vec![uxt(Alice, 209, true)]).unwrap();
+	fn transactions_too_far_ahead_should_be_rejected() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let options = Options { max_nonce_gap: Some(4), ..Default::default() };
+		let pool = TransactionPool::new(options, api.clone());
 
-		let ready = Ready::create(TestPolkadotApi.check_id(BlockId::number(0)).unwrap(), &TestPolkadotApi);
-		let pending: Vec<_> = pool.cull_and_get_pending(ready, |p| p.map(|a| (a.sender().ok(), a.index())).collect());
-		assert_eq!(pending, vec![(Some(Alice.to_raw_public().into()), 209)]);
+		pool.import_unchecked_extrinsic(BlockId::number(0), uxt(Alice, 213, true)).unwrap();
+		match *pool.import_unchecked_extrinsic(BlockId::number(0), uxt(Alice, 1_000_000, true)).unwrap_err().kind() {
+			ErrorKind::NonceTooFarAhead(index, on_chain) => assert_eq!((index, on_chain), (1_000_000, 209)),
+			ref e => panic!("unexpected error: {:?}", e),
+		}
+		// the gap is measured from the on-chain index at the given block.
+		pool.import_unchecked_extrinsic(BlockId::number(1), uxt(Alice, 214, true)).unwrap();
+		assert_eq!(pool.light_status().transaction_count, 2);
 	}
 
 	#[test]
-	fn index_submission_should_work() {
-		let pool = TransactionPool::new(Default::default());
-		pool.submit(vec![uxt(Alice, 209, false)]).unwrap();
+	fn generation_should_advance_with_changes_only() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let pool = TransactionPool::new(Default::default(), api.clone());
+		let ready = || Ready::create(api.check_id(BlockId::number(0)).unwrap(), &*api);
+		let initial = pool.generation();
 
-		let ready = Ready::create(TestPolkadotApi.check_id(BlockId::number(0)).unwrap(), &TestPolkadotApi);
-		let pending: Vec<_> = pool.cull_and_get_pending(ready, |p| p.map(|a| (a.sender().ok(), a.index())).collect());
-		assert_eq!(pending, vec![(Some(Alice.to_raw_public().into()), 209)]);
+		let xt = pool.import_unchecked_extrinsic(BlockId::number(0), uxt(Alice, 209, true)).unwrap();
+		let imported = pool.generation();
+		assert!(imported > initial);
+
+		pool.cull_and_get_pending(ready(), |pending| pending.count());
+		pool.cull_and_get_pending(ready(), |pending| pending.count());
+		assert_eq!(pool.generation(), imported);
+
+		pool.remove_invalid(&[*xt.hash()]);
+		assert!(pool.generation() > imported);
 	}
 
 	#[test]
-	fn multiple_id_submission_should_work() {
-		let pool = TransactionPool::new(Default::default());
-		pool.submit(vec![uxt(Alice, 209, true)]).unwrap();
-		pool.submit(vec![uxt(Alice, 210, true)]).unwrap();
+	fn quick_screen_should_reject_forged_signatures() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let pool = TransactionPool::new(Default::default(), api.clone());
+		pool.quick_screen(&uxt(Alice, 209, true)).unwrap();
+		pool.quick_screen(&uxt(Alice, 209, false)).unwrap();
 
-		let ready = Ready::create(TestPolkadotApi.check_id(BlockId::number(0)).unwrap(), &TestPolkadotApi);
-		let pending: Vec<_> = pool.cull_and_get_pending(ready, |p| p.map(|a| (a.sender().ok(), a.index())).collect());
-		assert_eq!(pending, vec![(Some(Alice.to_raw_public().into()), 209), (Some(Alice.to_raw_public().into()), 210)]);
+		let mut encoded = uxt(Alice, 209, true).encode();
+		let last = encoded.len() - 1;
+		encoded[last] ^= 1;
+		let forged = decode_extrinsic(&encoded).unwrap();
+		match *pool.quick_screen(&forged).unwrap_err().kind() {
+			ErrorKind::BadSignature(_) => {},
+			ref e => panic!("unexpected error: {:?}", e),
+		}
+		// nothing was looked up.
+		assert_eq!(api.lookups(), 0);
+		assert_eq!(pool.light_status().transaction_count, 0);
 	}
 
 	#[test]
-	fn multiple_index_submission_should_work() {
-		let pool = TransactionPool::new(Default::default());
-		pool.submit(vec![uxt(Alice, 209, false)]).unwrap();
-		pool.submit(vec![uxt(Alice, 210, false)]).unwrap();
+	fn persisted_transactions_should_be_loaded_back() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let pool = TransactionPool::new(Default::default(), api.clone());
+		let first = pool.import_unchecked_extrinsic(BlockId::number(0), uxt(Alice, 209, true)).unwrap();
+		let second = pool.import_unchecked_extrinsic(BlockId::number(0), uxt(Bob, 216, true)).unwrap();
 
-		let ready = Ready::create(TestPolkadotApi.check_id(BlockId::number(0)).unwrap(), &TestPolkadotApi);
-		let pending: Vec<_> = pool.cull_and_get_pending(ready, |p| p.map(|a| (a.sender().ok(), a.index())).collect());
-		assert_eq!(pending, vec![(Some(Alice.to_raw_public().into()), 209), (Some(Alice.to_raw_public().into()), 210)]);
+		let mut persisted = Vec::new();
+		pool.persist(&mut persisted).unwrap();
+		pool.clear();
+		assert_eq!(pool.light_status().transaction_count, 0);
+
+		assert_eq!(pool.load(BlockId::number(0), &persisted[..]).unwrap(), 2);
+		assert!(pool.contains(first.hash()));
+		assert!(pool.contains(second.hash()));
+
+		// those failing to import are skipped.
+		pool.remove_invalid(&[*second.hash()]);
+		assert_eq!(pool.load(BlockId::number(0), &persisted[..]).unwrap(), 1);
+		assert_eq!(pool.light_status().transaction_count, 2);
 	}
 
 	#[test]
-	fn id_based_early_nonce_should_be_culled() {
-		let pool = TransactionPool::new(Default::default());
-		pool.submit(vec![uxt(Alice, 208, true)]).unwrap();
+	fn generation_should_be_left_alone_by_queries() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let options = Options { allow_replace: true, ..Default::default() };
+		let pool = TransactionPool::new(options, api.clone());
+		pool.import_unchecked_extrinsic(BlockId::number(0), uxt(Alice, 209, true)).unwrap();
+		let generation = pool.generation();
 
-		let ready = Ready::create(TestPolkadotApi.check_id(BlockId::number(0)).unwrap(), &TestPolkadotApi);
-		let pending: Vec<_> = pool.cull_and_get_pending(ready, |p| p.map(|a| (a.sender().ok(), a.index())).collect());
-		assert_eq!(pending, vec![]);
+		pool.all_transactions();
+		pool.light_status();
+		pool.sender_histogram();
+		pool.pending_at(BlockId::number(0)).unwrap();
+		pool.export_valid_at(BlockId::number(0)).unwrap();
+		assert_eq!(pool.generation(), generation);
+
+		let bump = uxt_with_call(Alice, 209, true, Call::Timestamp(TimestampCall::set(1)));
+		pool.import_unchecked_extrinsic(BlockId::number(0), bump).unwrap();
+		assert!(pool.generation() > generation);
 	}
 
 	#[test]
-	fn index_based_early_nonce_should_be_culled() {
-		let pool = TransactionPool::new(Default::default());
-		pool.submit(vec![uxt(Alice, 208, false)]).unwrap();
+	fn culling_should_forget_what_was_cached_about_stale_transactions() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let pool = TransactionPool::new(Default::default(), api.clone());
+		let ready = |block| Ready::create(api.check_id(BlockId::number(block)).unwrap(), &*api);
 
-		let ready = Ready::create(TestPolkadotApi.check_id(BlockId::number(0)).unwrap(), &TestPolkadotApi);
-		let pending: Vec<_> = pool.cull_and_get_pending(ready, |p| p.map(|a| (a.sender().ok(), a.index())).collect());
-		assert_eq!(pending, vec![]);
+		pool.import_unchecked_extrinsic(BlockId::number(0), uxt(Bob, 214, false)).unwrap();
+		assert_eq!(api.lookups(), 1);
+		assert_eq!(pool.cull(None, ready(0)), 1);
+
+		// Bob's address is looked up afresh.
+		pool.import_unchecked_extrinsic(BlockId::number(0), uxt(Bob, 215, false)).unwrap();
+		assert_eq!(api.lookups(), 2);
+
+		// Bob's index advances by block 1, so his transaction is culled, and the one
+		// following it is judged against his index there.
+		assert_eq!(pool.cull(None, ready(1)), 1);
+		let next = pool.import_unchecked_extrinsic(BlockId::number(1), uxt(Bob, 216, true)).unwrap();
+		let pending: Vec<_> = pool.cull_and_get_pending(ready(1), |p| p.map(|xt| *xt.hash()).collect());
+		assert_eq!(pending, vec![*next.hash()]);
+	}
+
+	// fetcher whose fetches complete once the test says so, resolving index `0` to Alice
+	// and any other to nobody.
+	#[derive(Default)]
+	struct ManualFetcher {
+		pending: Mutex<Vec<(AccountIndex, futures::sync::oneshot::Sender<Option<AccountId>>)>>,
+	}
+
+	impl ManualFetcher {
+		fn fetched(&self) -> Vec<AccountIndex> {
+			let mut fetched = self.pending.lock().iter().map(|&(index, _)| index).collect::<Vec<_>>();
+			fetched.sort();
+			fetched
+		}
+
+		fn complete(&self) {
+			for (index, sender) in self.pending.lock().drain(..) {
+				let _ = sender.send(if index == 0 { Some(Alice.to_raw_public().into()) } else { None });
+			}
+		}
+	}
+
+	impl SenderFetcher for ManualFetcher {
+		type Fetch = Box<Future<Item=Option<AccountId>, Error=Error>>;
+
+		fn fetch_sender(&self, _at: BlockId, index: AccountIndex) -> Self::Fetch {
+			let (sender, receiver) = futures::sync::oneshot::channel();
+			self.pending.lock().push((index, sender));
+			Box::new(receiver.map_err(|_| "fetch cancelled".into()))
+		}
 	}
 
 	#[test]
-	fn id_based_late_nonce_should_be_queued() {
-		let pool = TransactionPool::new(Default::default());
-		let ready = || Ready::create(TestPolkadotApi.check_id(BlockId::number(0)).unwrap(), &TestPolkadotApi);
+	fn fetched_senders_should_promote_unverified_transactions() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let options = Options { drop_unverified_after: Some(1), ..Default::default() };
+		let pool = Arc::new(TransactionPool::new(options, api.clone()));
+		let xt = pool.import_unverified(uxt(Alice, 209, false)).unwrap();
+		let other = pool.import_unverified(uxt(Alice, 210, false)).unwrap();
+		let unknown = pool.import_unverified(uxt(Bob, 215, false)).unwrap();
+		pool.import_unverified(uxt(Alice, 211, true)).unwrap();
+		assert!(!xt.is_really_verified());
 
-		pool.submit(vec![uxt(Alice, 210, true)]).unwrap();
-		let pending: Vec<_> = pool.cull_and_get_pending(ready(), |p| p.map(|a| (a.sender().ok(), a.index())).collect());
-		assert_eq!(pending, vec![]);
+		let fetcher = ManualFetcher::default();
+		let first = TransactionPool::fetch_senders(pool.clone(), &fetcher, BlockId::number(0));
+		// the fetches in flight aren't started again on the next block.
+		let second = TransactionPool::fetch_senders(pool.clone(), &fetcher, BlockId::number(1));
+		assert_eq!(fetcher.fetched(), vec![0, 1]);
 
-		pool.submit(vec![uxt(Alice, 209, true)]).unwrap();
-		let pending: Vec<_> = pool.cull_and_get_pending(ready(), |p| p.map(|a| (a.sender().ok(), a.index())).collect());
-		assert_eq!(pending, vec![(Some(Alice.to_raw_public().into()), 209), (Some(Alice.to_raw_public().into()), 210)]);
+		fetcher.complete();
+		assert_eq!(first.wait().unwrap(), 2);
+		assert_eq!(second.wait().unwrap(), 0);
+		assert!(xt.is_really_verified());
+		assert!(other.is_really_verified());
+		// nothing was looked up locally, and the sender not found had no failure counted.
+		assert_eq!(api.lookups(), 0);
+		assert!(pool.contains(unknown.hash()));
+		assert!(!unknown.is_really_verified());
+
+		// done fetches may be started again.
+		TransactionPool::fetch_senders(pool.clone(), &fetcher, BlockId::number(1));
+		assert_eq!(fetcher.fetched(), vec![1]);
 	}
 
 	#[test]
-	fn index_based_late_nonce_should_be_queued() {
-		let pool = TransactionPool::new(Default::default());
-		let ready = || Ready::create(TestPolkadotApi.check_id(BlockId::number(0)).unwrap(), &TestPolkadotApi);
+	fn encoded_transaction_should_hash_to_its_hash() {
+		let xt = VerifiedTransaction::create(uxt(Alice, 209, true), usize::max_value()).unwrap();
+		assert_eq!(BlakeTwo256::hash(xt.encoded()), *xt.hash());
+		assert_eq!(xt.encoded(), &xt.as_transaction().encode()[..]);
+		assert_eq!(xt.encoded_size(), xt.encoded().len());
+	}
 
-		pool.submit(vec![uxt(Alice, 210, false)]).unwrap();
-		let pending: Vec<_> = pool.cull_and_get_pending(ready(), |p| p.map(|a| (a.sender().ok(), a.index())).collect());
-		assert_eq!(pending, vec![]);
+	#[test]
+	fn full_pool_should_reject_future_transactions_when_configured() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let options = Options {
+			limits: Limits { max_count: 2, ..Default::default() },
+			reject_future_when_full: true,
+			..Default::default()
+		};
+		let pool = TransactionPool::new(options, api.clone());
+		let ready = pool.import_unchecked_extrinsic(BlockId::number(0), uxt(Alice, 209, true)).unwrap();
+		let future = pool.import_unchecked_extrinsic(BlockId::number(0), uxt(Bob, 217, true)).unwrap();
 
-		pool.submit(vec![uxt(Alice, 209, false)]).unwrap();
-		let pending: Vec<_> = pool.cull_and_get_pending(ready(), |p| p.map(|a| (a.sender().ok(), a.index())).collect());
-		assert_eq!(pending, vec![(Some(Alice.to_raw_public().into()), 209), (Some(Alice.to_raw_public().into()), 210)]);
+		// follows Alice's pooled transaction, so it's ready too.
+		let next = pool.import_unchecked_extrinsic(BlockId::number(0), uxt(Alice, 210, true)).unwrap();
+		match *pool.import_unchecked_extrinsic(BlockId::number(0), uxt(Charlie, 170, true)).unwrap_err().kind() {
+			ErrorKind::PoolFull => {},
+			ref e => panic!("unexpected error: {:?}", e),
+		}
+		// no future transaction is left to make room.
+		match *pool.import_unchecked_extrinsic(BlockId::number(0), uxt(Dave, 191, true)).unwrap_err().kind() {
+			ErrorKind::PoolFull => {},
+			ref e => panic!("unexpected error: {:?}", e),
+		}
+
+		assert_eq!(pool.light_status().transaction_count, 2);
+		assert!(pool.contains(ready.hash()));
+		assert!(pool.contains(next.hash()));
+		assert!(!pool.contains(future.hash()));
 	}
 
 	#[test]
-	fn index_then_id_submission_should_make_progress() {
-		let pool = TransactionPool::new(Default::default());
-		pool.submit(vec![uxt(Alice, 209, false)]).unwrap();
-		pool.submit(vec![uxt(Alice, 210, true)]).unwrap();
+	fn full_pool_should_evict_only_transactions_still_future() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let options = Options {
+			limits: Limits { max_count: 3, ..Default::default() },
+			reject_future_when_full: true,
+			..Default::default()
+		};
+		let pool = TransactionPool::new(options, api.clone());
+		// future when imported, and older than Bob's, but ready once the gap is filled.
+		let follower = pool.import_unchecked_extrinsic(BlockId::number(0), uxt(Alice, 210, true)).unwrap();
+		let future = pool.import_unchecked_extrinsic(BlockId::number(0), uxt(Bob, 217, true)).unwrap();
+		pool.import_unchecked_extrinsic(BlockId::number(0), uxt(Alice, 209, true)).unwrap();
 
-		let ready = Ready::create(TestPolkadotApi.check_id(BlockId::number(0)).unwrap(), &TestPolkadotApi);
-		let pending: Vec<_> = pool.cull_and_get_pending(ready, |p| p.map(|a| (a.sender().ok(), a.index())).collect());
-		assert_eq!(pending, vec![
-			(Some(Alice.to_raw_public().into()), 209)
-		]);
+		let ready = pool.import_unchecked_extrinsic(BlockId::number(0), uxt(Charlie, 163, true)).unwrap();
+		assert_eq!(pool.light_status().transaction_count, 3);
+		assert!(pool.contains(follower.hash()));
+		assert!(!pool.contains(future.hash()));
+		assert!(pool.contains(ready.hash()));
 	}
 
 	#[test]
-	fn id_then_index_submission_should_make_progress() {
-		let pool = TransactionPool::new(Default::default());
-		pool.submit(vec![uxt(Alice, 209, true)]).unwrap();
-		pool.submit(vec![uxt(Alice, 210, false)]).unwrap();
+	fn authoring_order_should_be_deterministic() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let ready = || Ready::create(api.check_id(BlockId::number(0)).unwrap(), &*api);
+		let xts = vec![uxt(Bob, 217, true), uxt(Alice, 210, true), uxt(Bob, 215, true), uxt(Alice, 209, true), uxt(Bob, 216, true)];
 
-		let ready = Ready::create(TestPolkadotApi.check_id(BlockId::number(0)).unwrap(), &TestPolkadotApi);
-		let pending: Vec<_> = pool.cull_and_get_pending(ready, |p| p.map(|a| (a.sender().ok(), a.index())).collect());
-		assert_eq!(pending, vec![
-			(Some(Alice.to_raw_public().into()), 209)
-		]);
+		let ordered = |xts: Vec<UncheckedExtrinsic>| {
+			let pool = TransactionPool::new(Default::default(), api.clone());
+			for xt in xts {
+				pool.import_unchecked_extrinsic(BlockId::number(0), xt).unwrap();
+			}
+			pool.ready_ordered_for_authoring(ready())
+				.into_iter()
+				.map(|xt| (xt.sender().unwrap()[0], xt.index()))
+				.collect::<Vec<_>>()
+		};
+
+		// Alice's account ID comes before Bob's.
+		let expected = vec![(209, 209), (215, 215), (209, 210), (215, 216), (215, 217)];
+		assert_eq!(ordered(xts.clone()), expected);
+		assert_eq!(ordered(xts.into_iter().rev().collect()), expected);
 	}
 
 	#[test]
-	fn index_change_should_result_in_second_tx_culled_or_future() {
-		let pool = TransactionPool::new(Default::default());
-		pool.submit(vec![uxt(Alice, 209, false)]).unwrap();
-		pool.submit(vec![uxt(Alice, 210, false)]).unwrap();
+	fn bad_signatures_should_be_rejected_while_unknown_senders_are_deferred() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let pool = TransactionPool::new(Default::default(), api.clone());
 
-		let ready = Ready::create(TestPolkadotApi.check_id(BlockId::number(0)).unwrap(), &TestPolkadotApi);
-		let pending: Vec<_> = pool.cull_and_get_pending(ready, |p| p.map(|a| (a.sender().ok(), a.index())).collect());
-		assert_eq!(pending, vec![
-			(Some(Alice.to_raw_public().into()), 209),
-			(Some(Alice.to_raw_public().into()), 210)
-		]);
+		let mut forged = uxt(Alice, 209, true);
+		forged.extrinsic.index = 210;
+		match *pool.import_unchecked_extrinsic(BlockId::number(0), forged).unwrap_err().kind() {
+			ErrorKind::BadSignature(_) => {},
+			ref e => panic!("unexpected error: {:?}", e),
+		}
 
-		// first xt is mined, but that has a side-effect of switching index 0 from Alice to Bob.
-		// second xt now invalid signature, so it fails.
+		// no account has this index yet.
+		let mut unknown = uxt(Alice, 209, false);
+		unknown.extrinsic.signed = RawAddress::Index(9);
+		let deferred = pool.import_unchecked_extrinsic(BlockId::number(0), unknown).unwrap();
+		assert!(!deferred.is_really_verified());
+		assert_eq!(pool.needs_reverification(), vec![*deferred.hash()]);
 
-		// there is no way of reporting this back to the queue right now (TODO). this should cause
-		// the queue to flush all information regarding the sender index/account.
+		match super::check_error("invalid account index") {
+			ErrorKind::CheckFailed(_) => {},
+			ref e => panic!("unexpected error: {:?}", e),
+		}
+	}
 
-		// after this, a re-evaluation of the second's readiness should result in it being thrown
-		// out (or maybe placed in future queue).
-/*
-		// TODO: uncomment once the new queue design is in.
-		let ready = Ready::create(TestPolkadotApi.check_id(BlockId::number(1)).unwrap(), &TestPolkadotApi);
-		let pending: Vec<_> = pool.cull_and_get_pending(ready, |p| p.map(|a| (a.sender().ok(), a.index())).collect());
-		assert_eq!(pending, vec![]);
-*/
+	#[test]
+	fn contains_should_tell_pooled_transactions() {
+		let api = Arc::new(TestPolkadotApi::default());
+		let pool = TransactionPool::new(Default::default(), api.clone());
+		let xt = pool.import_unchecked_extrinsic(BlockId::number(0), uxt(Alice, 209, true)).unwrap();
+		let checks = api.check_ids();
+
+		assert!(pool.contains(xt.hash()));
+		assert!(!pool.contains(&[1; 32].into()));
+		assert_eq!(api.check_ids(), checks);
 	}
 }
@@ -23,13 +23,49 @@ use txpool;
 
 use watcher;
 
-#[derive(Default)]
-pub struct Listener<H: ::std::hash::Hash + Eq> {
-	watchers: HashMap<H, watcher::Sender<H>>
+pub struct Listener<H: ::std::hash::Hash + Eq, T> {
+	watchers: HashMap<H, watcher::Sender<H>>,
+	// the extrinsics currently in the pool, following every addition and removal.
+	pooled: HashMap<H, Arc<T>>,
+	// the extrinsic replaced by the one added last, if any.
+	replaced: Option<Arc<T>>,
+	// whether the extrinsics being removed were included in a block rather than canceled.
+	removing_included: bool,
 }
 
-impl<H: ::std::hash::Hash + Eq + Copy + fmt::Debug + fmt::LowerHex + Default> Listener<H> {
-	pub fn create_watcher<T: txpool::VerifiedTransaction<Hash=H>>(&mut self, xt: Arc<T>) -> watcher::Watcher<H> {
+impl<H: ::std::hash::Hash + Eq, T> Default for Listener<H, T> {
+	fn default() -> Self {
+		Listener {
+			watchers: Default::default(),
+			pooled: Default::default(),
+			replaced: None,
+			removing_included: false,
+		}
+	}
+}
+
+impl<H: ::std::hash::Hash + Eq + Copy + fmt::Debug + fmt::LowerHex + Default, T> Listener<H, T> {
+	/// Find an extrinsic in the pool by its hash.
+	pub fn find(&self, hash: &H) -> Option<Arc<T>> {
+		self.pooled.get(hash).cloned()
+	}
+
+	/// Whether the extrinsic with given hash is in the pool.
+	pub fn contains(&self, hash: &H) -> bool {
+		self.pooled.contains_key(hash)
+	}
+
+	/// Take the extrinsic which the one added last replaced, if any.
+	pub fn take_replaced(&mut self) -> Option<Arc<T>> {
+		self.replaced.take()
+	}
+
+	/// Treat the extrinsics removed from now on as included in a block rather than canceled.
+	pub fn set_removing_included(&mut self, removing_included: bool) {
+		self.removing_included = removing_included;
+	}
+
+	pub fn create_watcher<X: txpool::VerifiedTransaction<Hash=H>>(&mut self, xt: Arc<X>) -> watcher::Watcher<H> {
 		let sender = self.watchers.entry(*xt.hash()).or_insert_with(watcher::Sender::default);
 		sender.new_watcher()
 	}
@@ -38,6 +74,10 @@ impl<H: ::std::hash::Hash + Eq + Copy + fmt::Debug + fmt::LowerHex + Default> Li
 		self.fire(hash, |watcher| watcher.broadcast(peers));
 	}
 
+	pub fn evicted(&mut self, hash: &H) {
+		self.fire(hash, |watcher| watcher.dropped());
+	}
+
 	fn fire<F>(&mut self, hash: &H, fun: F) where F: FnOnce(&mut watcher::Sender<H>) {
 		let clean = if let Some(h) = self.watchers.get_mut(hash) {
 			fun(h);
@@ -52,18 +92,22 @@ impl<H: ::std::hash::Hash + Eq + Copy + fmt::Debug + fmt::LowerHex + Default> Li
 	}
 }
 
-impl<H, T> txpool::Listener<T> for Listener<H> where
+impl<H, T> txpool::Listener<T> for Listener<H, T> where
 	H: ::std::hash::Hash + Eq + Copy + fmt::Debug + fmt::LowerHex + Default,
 	T: txpool::VerifiedTransaction<Hash=H>,
 {
 	fn added(&mut self, tx: &Arc<T>, old: Option<&Arc<T>>) {
+		self.pooled.insert(*tx.hash(), tx.clone());
+		self.replaced = old.cloned();
 		if let Some(old) = old {
 			let hash = tx.hash();
+			self.pooled.remove(old.hash());
 			self.fire(old.hash(), |watcher| watcher.usurped(*hash));
 		}
 	}
 
 	fn dropped(&mut self, tx: &Arc<T>, by: Option<&T>) {
+		self.pooled.remove(tx.hash());
 		self.fire(tx.hash(), |watcher| match by {
 			Some(t) => watcher.usurped(*t.hash()),
 			None => watcher.dropped(),
@@ -75,14 +119,20 @@ impl<H, T> txpool::Listener<T> for Listener<H> where
 	}
 
 	fn invalid(&mut self, tx: &Arc<T>) {
+		self.pooled.remove(tx.hash());
 		warn!("Extrinsic invalid: {:?}", tx);
 	}
 
 	fn canceled(&mut self, tx: &Arc<T>) {
+		if self.removing_included {
+			return txpool::Listener::mined(self, tx);
+		}
+		self.pooled.remove(tx.hash());
 		warn!("Extrinsic canceled: {:?}", tx);
 	}
 
 	fn mined(&mut self, tx: &Arc<T>) {
+		self.pooled.remove(tx.hash());
 		// TODO [ToDr] latest block number?
 		let header_hash = Default::default();
 		self.fire(tx.hash(), |watcher| watcher.finalised(header_hash))
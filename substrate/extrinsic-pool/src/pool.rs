@@ -18,16 +18,73 @@ use std::{
 	collections::HashMap,
 	fmt,
 	marker::PhantomData,
-	sync::{Arc, Weak},
+	sync::{Arc, Weak, atomic::{AtomicUsize, Ordering}},
+	time::{Duration, Instant},
 };
 
 use futures::sync::mpsc;
-use parking_lot::{RwLock, Mutex};
-use txpool;
+use parking_lot::{RwLock, RwLockReadGuard, RwLockWriteGuard, Mutex};
+use txpool::{self, VerifiedTransaction};
 
 use listener::Listener;
 use watcher::Watcher;
 
+/// Time spent waiting for the lock guarding the pool.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ContentionStats {
+	/// Number of times the lock was acquired.
+	pub acquisitions: u64,
+	/// Number of acquisitions which had to wait for another holder.
+	pub contended: u64,
+	/// Total time spent waiting.
+	pub total_wait: Duration,
+	/// Longest single wait.
+	pub max_wait: Duration,
+}
+
+/// Source of the current time, used to measure waits for the pool's lock.
+pub type Clock = Box<Fn() -> Instant + Send + Sync>;
+
+// counters behind `ContentionStats`, updated without taking a lock of their own. Waits are
+// kept in nanoseconds.
+#[derive(Default)]
+struct Contention {
+	acquisitions: AtomicUsize,
+	contended: AtomicUsize,
+	total_wait: AtomicUsize,
+	max_wait: AtomicUsize,
+}
+
+impl Contention {
+	fn note(&self, wait: Option<Duration>) {
+		self.acquisitions.fetch_add(1, Ordering::Relaxed);
+		if let Some(wait) = wait {
+			let wait = (wait.as_secs() as usize)
+				.saturating_mul(1_000_000_000)
+				.saturating_add(wait.subsec_nanos() as usize);
+			self.contended.fetch_add(1, Ordering::Relaxed);
+			self.total_wait.fetch_add(wait, Ordering::Relaxed);
+			let mut max_wait = self.max_wait.load(Ordering::Relaxed);
+			while wait > max_wait {
+				match self.max_wait.compare_exchange(max_wait, wait, Ordering::Relaxed, Ordering::Relaxed) {
+					Ok(_) => break,
+					Err(current) => max_wait = current,
+				}
+			}
+		}
+	}
+
+	fn stats(&self) -> ContentionStats {
+		let duration = |nanos: usize| Duration::new((nanos / 1_000_000_000) as u64, (nanos % 1_000_000_000) as u32);
+		ContentionStats {
+			acquisitions: self.acquisitions.load(Ordering::Relaxed) as u64,
+			contended: self.contended.load(Ordering::Relaxed) as u64,
+			total_wait: duration(self.total_wait.load(Ordering::Relaxed)),
+			max_wait: duration(self.max_wait.load(Ordering::Relaxed)),
+		}
+	}
+}
+
 /// Extrinsics pool.
 pub struct Pool<Ex, Hash, V, S, E> where
 	Hash: ::std::hash::Hash + Eq + Copy + fmt::Debug + fmt::LowerHex,
@@ -38,10 +95,12 @@ pub struct Pool<Ex, Hash, V, S, E> where
 	pool: RwLock<txpool::Pool<
 		V::VerifiedTransaction,
 		S,
-		Listener<Hash>,
+		Listener<Hash, V::VerifiedTransaction>,
 	>>,
 	verifier: V,
 	import_notification_sinks: Mutex<Vec<mpsc::UnboundedSender<Weak<V::VerifiedTransaction>>>>,
+	contention: Contention,
+	clock: Clock,
 }
 
 impl<Ex, Hash, V, S, E> Pool<Ex, Hash, V, S, E> where
@@ -59,18 +118,65 @@ impl<Ex, Hash, V, S, E> Pool<Ex, Hash, V, S, E> where
 			pool: RwLock::new(txpool::Pool::new(Listener::default(), scoring, options)),
 			verifier,
 			import_notification_sinks: Default::default(),
+			contention: Default::default(),
+			clock: Box::new(Instant::now),
+		}
+	}
+
+	/// Measure waits for the pool's lock with the given clock rather than `Instant::now`.
+	pub fn with_clock(mut self, clock: Clock) -> Self {
+		self.clock = clock;
+		self
+	}
+
+	/// Get the statistics of waiting for the pool's lock since the pool was created.
+	pub fn lock_contention_stats(&self) -> ContentionStats {
+		self.contention.stats()
+	}
+
+	fn read_pool(&self) -> RwLockReadGuard<txpool::Pool<V::VerifiedTransaction, S, Listener<Hash, V::VerifiedTransaction>>> {
+		if let Some(pool) = self.pool.try_read() {
+			self.contention.note(None);
+			return pool;
+		}
+
+		let started = (self.clock)();
+		let pool = self.pool.read();
+		self.contention.note(Some((self.clock)().duration_since(started)));
+		pool
+	}
+
+	fn write_pool(&self) -> RwLockWriteGuard<txpool::Pool<V::VerifiedTransaction, S, Listener<Hash, V::VerifiedTransaction>>> {
+		if let Some(pool) = self.pool.try_write() {
+			self.contention.note(None);
+			return pool;
 		}
+
+		let started = (self.clock)();
+		let pool = self.pool.write();
+		self.contention.note(Some((self.clock)().duration_since(started)));
+		pool
 	}
 
 	/// Imports a pre-verified extrinsic to the pool.
 	pub fn import(&self, xt: V::VerifiedTransaction) -> Result<Arc<V::VerifiedTransaction>, E> {
-		let result = self.pool.write().import(xt)?;
+		self.import_replacing(xt).map(|(result, _)| result)
+	}
+
+	/// Imports a pre-verified extrinsic to the pool, also returning the extrinsic it
+	/// replaced, if any.
+	pub fn import_replacing(&self, xt: V::VerifiedTransaction) -> Result<(Arc<V::VerifiedTransaction>, Option<Arc<V::VerifiedTransaction>>), E> {
+		let (result, replaced) = {
+			let mut pool = self.write_pool();
+			let result = pool.import(xt)?;
+			(result, pool.listener_mut().take_replaced())
+		};
 
 		let weak = Arc::downgrade(&result);
 		self.import_notification_sinks.lock()
 			.retain(|sink| sink.unbounded_send(weak.clone()).is_ok());
 
-		Ok(result)
+		Ok((result, replaced))
 	}
 
 	/// Return an event stream of transactions imported to the pool.
@@ -83,7 +189,7 @@ impl<Ex, Hash, V, S, E> Pool<Ex, Hash, V, S, E> where
 	/// Invoked when extrinsics are broadcasted.
 	pub fn on_broadcasted(&self, propagated: HashMap<Hash, Vec<String>>) {
 		for (hash, peers) in propagated.into_iter() {
-			self.pool.write().listener_mut().broadcasted(&hash, peers);
+			self.write_pool().listener_mut().broadcasted(&hash, peers);
 		}
 	}
 
@@ -93,7 +199,7 @@ impl<Ex, Hash, V, S, E> Pool<Ex, Hash, V, S, E> where
 			.into_iter()
 			.map(|xt| self.verifier.verify_transaction(xt))
 			.map(|xt| {
-				Ok(self.pool.write().import(xt?)?)
+				Ok(self.write_pool().import(xt?)?)
 			})
 			.collect()
 	}
@@ -101,43 +207,110 @@ impl<Ex, Hash, V, S, E> Pool<Ex, Hash, V, S, E> where
 	/// Import a single extrinsic and starts to watch their progress in the pool.
 	pub fn submit_and_watch(&self, xt: Ex) -> Result<Watcher<Hash>, E> {
 		let xt = self.submit(vec![xt])?.pop().expect("One extrinsic passed; one result returned; qed");
-		Ok(self.pool.write().listener_mut().create_watcher(xt))
+		Ok(self.write_pool().listener_mut().create_watcher(xt))
 	}
 
-	/// Remove from the pool.
-	pub fn remove(&self, hashes: &[Hash], is_valid: bool) -> Vec<Option<Arc<V::VerifiedTransaction>>> {
-		let mut pool = self.pool.write();
+	/// Remove from the pool, telling the listener whether the extrinsics turned out to be
+	/// invalid rather than being cancelled.
+	pub fn remove(&self, hashes: &[Hash], is_invalid: bool) -> Vec<Option<Arc<V::VerifiedTransaction>>> {
+		let mut pool = self.write_pool();
 		let mut results = Vec::with_capacity(hashes.len());
 		for hash in hashes {
-			results.push(pool.remove(hash, is_valid));
+			results.push(pool.remove(hash, is_invalid));
 		}
 		results
 	}
 
+	/// Remove extrinsics included in a block from the pool, telling the listener they were
+	/// mined.
+	pub fn remove_included(&self, hashes: &[Hash]) -> Vec<Option<Arc<V::VerifiedTransaction>>> {
+		let mut pool = self.write_pool();
+		pool.listener_mut().set_removing_included(true);
+		let results = hashes.iter()
+			.map(|hash| pool.remove(hash, false))
+			.collect();
+		pool.listener_mut().set_removing_included(false);
+		results
+	}
+
+	/// Remove from the pool, notifying watchers that the extrinsics were dropped.
+	///
+	/// Returns the number of extrinsics actually removed.
+	pub fn evict(&self, hashes: &[Hash]) -> usize {
+		let mut pool = self.write_pool();
+		let mut evicted = 0;
+		for hash in hashes {
+			if pool.remove(hash, false).is_some() {
+				pool.listener_mut().evicted(hash);
+				evicted += 1;
+			}
+		}
+		evicted
+	}
+
+	/// Remove all extrinsics for which the predicate returns `false`.
+	///
+	/// The predicate is called on a snapshot of the pool without holding its lock, so it may
+	/// query the pool itself. Extrinsics imported meanwhile are kept. Returns the extrinsics
+	/// actually removed.
+	pub fn retain<F>(&self, mut f: F) -> Vec<Arc<V::VerifiedTransaction>> where
+		F: FnMut(&Arc<V::VerifiedTransaction>) -> bool,
+	{
+		let snapshot = self.read_pool()
+			.pending(|_: &V::VerifiedTransaction| txpool::Readiness::Ready)
+			.collect::<Vec<_>>();
+		let to_remove = snapshot.into_iter()
+			.filter(|xt| !f(xt))
+			.collect::<Vec<_>>();
+
+		let mut pool = self.write_pool();
+		to_remove.into_iter()
+			.filter(|xt| pool.remove(xt.hash(), false).is_some())
+			.collect()
+	}
+
+	/// Find an extrinsic in the pool by its hash.
+	pub fn find(&self, hash: &Hash) -> Option<Arc<V::VerifiedTransaction>> {
+		self.read_pool().listener().find(hash)
+	}
+
+	/// Whether the extrinsic with given hash is in the pool.
+	pub fn contains(&self, hash: &Hash) -> bool {
+		self.read_pool().listener().contains(hash)
+	}
+
 	/// Cull transactions from the queue.
 	pub fn cull<R>(&self, senders: Option<&[<V::VerifiedTransaction as txpool::VerifiedTransaction>::Sender]>, ready: R) -> usize where
 		R: txpool::Ready<V::VerifiedTransaction>,
 	{
-		self.pool.write().cull(senders, ready)
+		self.write_pool().cull(senders, ready)
 	}
 
 	/// Cull transactions from the queue and then compute the pending set.
 	pub fn cull_and_get_pending<R, F, T>(&self, ready: R, f: F) -> T where
 		R: txpool::Ready<V::VerifiedTransaction> + Clone,
-		F: FnOnce(txpool::PendingIterator<V::VerifiedTransaction, R, S, Listener<Hash>>) -> T,
+		F: FnOnce(txpool::PendingIterator<V::VerifiedTransaction, R, S, Listener<Hash, V::VerifiedTransaction>>) -> T,
 	{
-		let mut pool = self.pool.write();
+		let mut pool = self.write_pool();
 		pool.cull(None, ready.clone());
 		f(pool.pending(ready))
 	}
 
+	/// Compute the pending set without culling the queue.
+	pub fn pending<R, F, T>(&self, ready: R, f: F) -> T where
+		R: txpool::Ready<V::VerifiedTransaction>,
+		F: FnOnce(txpool::PendingIterator<V::VerifiedTransaction, R, S, Listener<Hash, V::VerifiedTransaction>>) -> T,
+	{
+		f(self.read_pool().pending(ready))
+	}
+
 	/// Get the full status of the queue (including readiness)
 	pub fn status<R: txpool::Ready<V::VerifiedTransaction>>(&self, ready: R) -> txpool::Status {
-		self.pool.read().status(ready)
+		self.read_pool().status(ready)
 	}
 
 	/// Returns light status of the pool.
 	pub fn light_status(&self) -> txpool::LightStatus {
-		self.pool.read().light_status()
+		self.read_pool().light_status()
 	}
 }